@@ -193,41 +193,171 @@ fn generate_zk_message() -> ZkMessage {
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloy_primitives::hex;
 
     use super::*;
 
+    /// ABI encoding of the `ZkMessage` returned by the circuit, one 32 bytes word per line.
+    /// `functions[0].contractAddress` has to follow `FORWARDER_LIBRARY_CONTRACT`.
+    const GOLDEN_ZK_MESSAGE: &str = concat!(
+        // ZkMessage tuple offset
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        // registry
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // blockNumber
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // authorizationContract
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // processorMessage offset
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        // processorMessage.messageType = SendMsgs
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        // processorMessage.message offset
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        // processorMessage.message length
+        "00000000000000000000000000000000000000000000000000000000000002a0",
+        // SendMsgs tuple offset
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        // executionId
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        // priority = Medium
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // subroutine offset
+        "00000000000000000000000000000000000000000000000000000000000000a0",
+        // expirationTime
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // messages offset
+        "0000000000000000000000000000000000000000000000000000000000000200",
+        // subroutine.subroutineType = Atomic
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // subroutine.subroutine offset
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        // subroutine.subroutine length
+        "0000000000000000000000000000000000000000000000000000000000000100",
+        // AtomicSubroutine tuple offset
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        // functions offset
+        "00000000000000000000000000000000000000000000000000000000000000a0",
+        // retryLogic.times.retryType = NoRetry
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // retryLogic.times.amount
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // retryLogic.interval.durationType = Time
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        // retryLogic.interval.value
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // functions length
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        // functions[0].contractAddress
+        "0000000000000000000000007811a1648e43f1bc207d7df21b039ae9d2870b91",
+        // messages length
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        // messages[0] offset
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        // messages[0] length
+        "0000000000000000000000000000000000000000000000000000000000000004",
+        // messages[0] = forward() selector
+        "d264e05e00000000000000000000000000000000000000000000000000000000",
+    );
+
+    fn zk_message() -> ZkMessage {
+        ZkMessage::abi_decode(&circuit(vec![]))
+            .expect("circuit output should decode as a ZkMessage")
+    }
+
+    fn send_msgs() -> SendMsgs {
+        SendMsgs::abi_decode(&zk_message().processorMessage.message)
+            .expect("processor message should decode as SendMsgs")
+    }
+
+    fn atomic_subroutine() -> AtomicSubroutine {
+        AtomicSubroutine::abi_decode(&send_msgs().subroutine.subroutine)
+            .expect("subroutine should decode as an AtomicSubroutine")
+    }
+
     #[test]
-    fn test_circuit_valid_transfer() {
-        let fee_amount = 957u64; // Valid fee below threshold
-        let receiver = "0x33C4DaD158F1E2cCF97bF17d1574d5b7b9f43002";
-        let expiration = 1890000000000000u64; // Example expiration timestamp
+    fn test_circuit_output_matches_golden_vector() {
+        assert_eq!(
+            hex::encode(circuit(vec![])),
+            GOLDEN_ZK_MESSAGE,
+            "the encoding of the ZkMessage changed"
+        );
+    }
 
-        let witnesses = vec![
-            Witness::Data(fee_amount.to_le_bytes().to_vec()),
-            Witness::Data(receiver.as_bytes().to_vec()),
-            Witness::Data(expiration.to_le_bytes().to_vec()),
-        ];
+    #[test]
+    fn test_circuit_output_roundtrips() {
+        assert_eq!(zk_message().abi_encode(), circuit(vec![]));
+    }
+
+    #[test]
+    fn test_zk_message_fields() {
+        let message = zk_message();
+
+        assert_eq!(message.registry, 0);
+        assert_eq!(message.blockNumber, 0);
+        assert_eq!(message.authorizationContract, Address::ZERO);
+        assert_eq!(
+            message.processorMessage.messageType as u8,
+            ProcessorMessageType::SendMsgs as u8
+        );
+    }
+
+    #[test]
+    fn test_send_msgs_fields() {
+        let send_msgs = send_msgs();
+
+        assert_eq!(send_msgs.executionId, 1);
+        assert_eq!(send_msgs.priority as u8, Priority::Medium as u8);
+        assert_eq!(send_msgs.expirationTime, 0);
+        assert_eq!(
+            send_msgs.subroutine.subroutineType as u8,
+            SubroutineType::Atomic as u8
+        );
+
+        assert_eq!(send_msgs.messages.len(), 1);
+        assert_eq!(send_msgs.messages[0].as_ref(), forwardCall::SELECTOR);
+        assert!(forwardCall::abi_decode(&send_msgs.messages[0]).is_ok());
+    }
 
-        let result = circuit(witnesses);
+    #[test]
+    fn test_atomic_subroutine_fields() {
+        let subroutine = atomic_subroutine();
+
+        assert_eq!(subroutine.functions.len(), 1);
+        assert_eq!(
+            subroutine.functions[0].contractAddress,
+            FORWARDER_LIBRARY_CONTRACT.parse::<Address>().unwrap()
+        );
+
+        let retry_logic = subroutine.retryLogic;
+        assert_eq!(
+            retry_logic.times.retryType as u8,
+            RetryTimesType::NoRetry as u8
+        );
+        assert_eq!(retry_logic.times.amount, 0);
+        assert_eq!(
+            retry_logic.interval.durationType as u8,
+            DurationType::Time as u8
+        );
+        assert_eq!(retry_logic.interval.value, 0);
+    }
 
-        // Try to decode the ZkMessage to verify it's valid
-        let decoded_result = ZkMessage::abi_decode(&result);
-        assert!(decoded_result.is_ok(), "Should be able to decode ZkMessage");
+    #[test]
+    #[should_panic(expected = "Expected no witnesses")]
+    fn test_circuit_rejects_empty_witness() {
+        circuit(vec![Witness::Data(vec![])]);
     }
 
     #[test]
-    #[should_panic(expected = "Fee amount exceeds the maximum allowed limit of 1930")]
-    fn test_circuit_excessive_fees() {
-        let fee_amount = 2000u64; // Exceeds the maximum allowed fee
+    #[should_panic(expected = "Expected no witnesses")]
+    fn test_circuit_rejects_unexpected_witnesses() {
+        // Witnesses of a fee based transfer, which this circuit doesn't take
+        let fee_amount = 957u64;
         let receiver = "0x33C4DaD158F1E2cCF97bF17d1574d5b7b9f43002";
-        let expiration = 1890000000000000u64; // Example expiration timestamp
 
-        let witnesses = vec![
+        circuit(vec![
             Witness::Data(fee_amount.to_le_bytes().to_vec()),
             Witness::Data(receiver.as_bytes().to_vec()),
-            Witness::Data(expiration.to_le_bytes().to_vec()),
-        ];
-
-        circuit(witnesses);
+        ]);
     }
 }
@@ -2,38 +2,674 @@
 
 extern crate alloc;
 
-use alloc::{string::ToString as _, vec::Vec, vec};
-use serde_json::Value;
+use alloc::{
+    format,
+    string::{String, ToString as _},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+use serde_json::{json, Value};
 use valence_coprocessor::Witness;
 use valence_coprocessor_wasm::abi;
 
+/// Metadata of every stored proof, oldest first, and the id of the next one.
+pub const INDEX_PATH: &str = "/var/share/proofs.idx";
+
+/// Directory of the proofs delivered without a `payload.path`.
+pub const PROOFS_DIR: &str = "/var/share/proofs";
+
 pub fn get_witnesses(args: Value) -> anyhow::Result<Vec<Witness>> {
     abi::log!(
         "received a proof request with arguments {}",
         serde_json::to_string(&args).unwrap_or_default()
     )?;
 
+    witnesses(&args)
+}
+
+/// Computes the circuit witnesses from the proof request arguments.
+///
+/// Doesn't touch the coprocessor runtime, so it can also be executed natively (e.g. by the mock coprocessor).
+pub fn witnesses(_args: &Value) -> anyhow::Result<Vec<Witness>> {
     Ok(vec![])
 }
 
+/// Runs the entrypoint command in `payload.cmd`, see [`handle`].
+///
+/// Answers `{"ok": true, "result": ...}`, or `{"ok": false, "error": {"code": ..., "message": ...}}`
+/// when the command fails.
 pub fn entrypoint(args: Value) -> anyhow::Result<Value> {
     abi::log!(
         "received an entrypoint request with arguments {}",
         serde_json::to_string(&args).unwrap_or_default()
     )?;
 
-    let cmd = args["payload"]["cmd"].as_str().unwrap();
+    match handle(&mut CoprocessorStorage, &args) {
+        Ok(result) => Ok(json!({ "ok": true, "result": result })),
+        Err(error) => {
+            abi::log!("entrypoint request failed: {error}")?;
+            Ok(json!({ "ok": false, "error": error.to_value() }))
+        }
+    }
+}
+
+/// Files of the controller virtual filesystem.
+pub trait Storage {
+    /// Contents of the file at `path`, `None` if it doesn't exist or is empty.
+    fn read(&mut self, path: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    fn write(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()>;
+}
+
+/// The storage the coprocessor service keeps for the controller.
+pub struct CoprocessorStorage;
+
+impl Storage for CoprocessorStorage {
+    fn read(&mut self, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let bytes = abi::get_storage_file(path)?;
+
+        Ok(Some(bytes).filter(|bytes| !bytes.is_empty()))
+    }
+
+    fn write(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        abi::set_storage_file(path, bytes)?;
+
+        Ok(())
+    }
+}
+
+/// Error of an entrypoint command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntrypointError {
+    MissingField(&'static str),
+    InvalidField {
+        field: &'static str,
+        expected: &'static str,
+    },
+    UnknownCommand(String),
+    /// The coprocessor delivered a failed proof computation.
+    ProofFailed(String),
+    NotFound(String),
+    Storage(String),
+}
+
+impl EntrypointError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingField(_) => "missing-field",
+            Self::InvalidField { .. } => "invalid-field",
+            Self::UnknownCommand(_) => "unknown-command",
+            Self::ProofFailed(_) => "proof-failed",
+            Self::NotFound(_) => "not-found",
+            Self::Storage(_) => "storage",
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!({ "code": self.code(), "message": self.to_string() })
+    }
+}
+
+impl fmt::Display for EntrypointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing field {field}"),
+            Self::InvalidField { field, expected } => {
+                write!(f, "field {field} should be {expected}")
+            }
+            Self::UnknownCommand(cmd) => write!(
+                f,
+                "unknown command {cmd}, expected init, store, list, latest or delete"
+            ),
+            Self::ProofFailed(reason) => write!(f, "proof computation failed: {reason}"),
+            Self::NotFound(what) => write!(f, "{what} not found"),
+            Self::Storage(reason) => write!(f, "storage error: {reason}"),
+        }
+    }
+}
+
+type Result<T> = core::result::Result<T, EntrypointError>;
+
+/// Contents of [`INDEX_PATH`].
+///
+/// The next id is kept apart from the entries so that ids aren't reused once the proofs are
+/// deleted, which would overwrite the default proof files.
+#[derive(Debug, Clone, PartialEq)]
+struct Index {
+    next_id: u64,
+    proofs: Vec<ProofEntry>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            proofs: vec![],
+        }
+    }
+}
+
+/// Metadata of a stored proof, kept in [`INDEX_PATH`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofEntry {
+    /// Sequence number of the proof, starting at 1.
+    pub id: u64,
+    /// Id given by the requester in `payload.request_id`, `proof-<id>` otherwise.
+    pub request_id: String,
+    /// File holding the proof document as delivered by the coprocessor.
+    pub path: String,
+    /// Arguments of the proof request.
+    pub args: Value,
+    /// Unix time given by the requester in `payload.timestamp`, the controller has no clock.
+    pub timestamp: Option<u64>,
+}
+
+impl ProofEntry {
+    pub fn to_value(&self) -> Value {
+        json!({
+            "id": self.id,
+            "request_id": self.request_id,
+            "path": self.path,
+            "args": self.args,
+            "timestamp": self.timestamp,
+        })
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(Self {
+            id: value["id"].as_u64()?,
+            request_id: value["request_id"].as_str()?.to_string(),
+            path: value["path"].as_str()?.to_string(),
+            args: value["args"].clone(),
+            timestamp: value["timestamp"].as_u64(),
+        })
+    }
+}
+
+/// Runs an entrypoint command against `storage`.
+///
+/// - `init`: writes an empty proof index, once after the controller is deployed. An index
+///   that can be read is kept. If the read fails, the index is only written with
+///   `payload.force`, since the failure can't be told apart from a missing file.
+/// - `store`: called by the coprocessor with the proof document. Checks that the proof
+///   succeeded, writes the document to `payload.path` (or under [`PROOFS_DIR`]) and
+///   indexes it with `payload.request_id`, `payload.timestamp` and the request arguments.
+/// - `list`: metadata of the stored proofs, newest first, at most `payload.limit`.
+/// - `latest`: metadata and document of the most recent proof.
+/// - `delete`: forgets the proofs older than `payload.before` and all but the `payload.keep`
+///   most recent ones, emptying their files. Returns the deleted ids.
+pub fn handle(storage: &mut impl Storage, args: &Value) -> Result<Value> {
+    let payload = &args["payload"];
+    let cmd = payload["cmd"]
+        .as_str()
+        .ok_or(EntrypointError::MissingField("payload.cmd"))?;
 
     match cmd {
-        "store" => {
-            let path = args["payload"]["path"].as_str().unwrap().to_string();
-            let bytes = serde_json::to_vec(&args).unwrap();
+        "init" => {
+            let force = optional_bool(payload, "force", "payload.force")?.unwrap_or(false);
+            let index = match stored_index(storage) {
+                Ok(Some(index)) => index,
+                Ok(None) => {
+                    write_index(storage, &Index::default())?;
+                    Index::default()
+                }
+                Err(_) if force => {
+                    write_index(storage, &Index::default())?;
+                    Index::default()
+                }
+                Err(error) => return Err(error),
+            };
+
+            Ok(json!({ "next_id": index.next_id, "proofs": index.proofs.len() }))
+        }
+        "store" => store(storage, args).map(|entry| entry.to_value()),
+        "list" => {
+            let limit = optional_u64(payload, "limit", "payload.limit")?;
+            let index = read_index(storage)?;
+            let proofs: Vec<Value> = index
+                .proofs
+                .iter()
+                .rev()
+                .take(limit.map_or(usize::MAX, |limit| limit as usize))
+                .map(ProofEntry::to_value)
+                .collect();
+
+            Ok(json!({ "proofs": proofs }))
+        }
+        "latest" => {
+            let entry = read_index(storage)?
+                .proofs
+                .pop()
+                .ok_or_else(|| EntrypointError::NotFound("stored proof".to_string()))?;
+            let bytes = read(storage, &entry.path)?
+                .ok_or_else(|| EntrypointError::NotFound(format!("proof file {}", entry.path)))?;
+            let proof: Value = serde_json::from_slice(&bytes).map_err(|e| {
+                EntrypointError::Storage(format!("invalid proof file {}: {e}", entry.path))
+            })?;
+
+            Ok(json!({ "entry": entry.to_value(), "proof": proof }))
+        }
+        "delete" => {
+            let keep = optional_u64(payload, "keep", "payload.keep")?;
+            let before = optional_u64(payload, "before", "payload.before")?;
+            if keep.is_none() && before.is_none() {
+                return Err(EntrypointError::MissingField(
+                    "payload.keep or payload.before",
+                ));
+            }
+
+            delete(storage, keep, before).map(|deleted| json!({ "deleted": deleted }))
+        }
+        _ => Err(EntrypointError::UnknownCommand(cmd.to_string())),
+    }
+}
+
+fn store(storage: &mut impl Storage, document: &Value) -> Result<ProofEntry> {
+    let payload = &document["payload"];
+
+    if document["success"].as_bool() != Some(true) {
+        let reason = document["error"]
+            .as_str()
+            .unwrap_or("the coprocessor reported an unsuccessful proof");
+        return Err(EntrypointError::ProofFailed(reason.to_string()));
+    }
+    match document["proof"].as_str() {
+        Some(proof) if !proof.is_empty() => (),
+        Some(_) => {
+            return Err(EntrypointError::InvalidField {
+                field: "proof",
+                expected: "a non-empty string",
+            })
+        }
+        None => return Err(EntrypointError::MissingField("proof")),
+    }
+
+    let path = optional_str(payload, "path", "payload.path")?;
+    let request_id = optional_str(payload, "request_id", "payload.request_id")?;
+    let timestamp = optional_u64(payload, "timestamp", "payload.timestamp")?;
+
+    let mut index = read_index(storage)?;
+    let id = index.next_id;
+    let entry = ProofEntry {
+        id,
+        request_id: request_id.map_or_else(|| format!("proof-{id}"), str::to_string),
+        path: path.map_or_else(|| format!("{PROOFS_DIR}/{id:08}.bin"), str::to_string),
+        args: document["args"].clone(),
+        timestamp,
+    };
+
+    let bytes = serde_json::to_vec(document)
+        .map_err(|e| EntrypointError::Storage(format!("failed to serialize the proof: {e}")))?;
+    write(storage, &entry.path, &bytes)?;
+
+    index.next_id = id + 1;
+    index.proofs.push(entry.clone());
+    write_index(storage, &index)?;
+
+    Ok(entry)
+}
+
+fn delete(storage: &mut impl Storage, keep: Option<u64>, before: Option<u64>) -> Result<Vec<u64>> {
+    let index = read_index(storage)?;
+    let keep_from = keep.map_or(0, |keep| index.proofs.len().saturating_sub(keep as usize));
+
+    let (kept, deleted): (Vec<_>, Vec<_>) =
+        index
+            .proofs
+            .into_iter()
+            .enumerate()
+            .partition(|(position, entry)| {
+                let too_old = matches!(
+                    (before, entry.timestamp),
+                    (Some(before), Some(timestamp)) if timestamp < before
+                );
+                *position >= keep_from && !too_old
+            });
+    let kept: Vec<ProofEntry> = kept.into_iter().map(|(_, entry)| entry).collect();
+
+    write_index(
+        storage,
+        &Index {
+            next_id: index.next_id,
+            proofs: kept.clone(),
+        },
+    )?;
+
+    // Files can't be removed, only emptied. Several proofs may share a path, e.g. when
+    // every request stores to the same file, so files of kept proofs are left alone.
+    let mut ids = vec![];
+    for (_, entry) in deleted {
+        if !kept.iter().any(|other| other.path == entry.path) {
+            write(storage, &entry.path, &[])?;
+        }
+        ids.push(entry.id);
+    }
+
+    Ok(ids)
+}
+
+/// The proof index, empty until the first proof is stored.
+///
+/// A failed read is never taken for a missing index, it would be rewritten from scratch: on
+/// a service that can't read files that were never written, the index is created by `init`.
+fn read_index(storage: &mut impl Storage) -> Result<Index> {
+    match stored_index(storage) {
+        Ok(index) => Ok(index.unwrap_or_default()),
+        Err(EntrypointError::Storage(message)) => Err(EntrypointError::Storage(format!(
+            "{message}, run the init command if no proof was stored yet"
+        ))),
+        Err(error) => Err(error),
+    }
+}
 
-            abi::set_storage_file(&path, &bytes).unwrap();
+fn stored_index(storage: &mut impl Storage) -> Result<Option<Index>> {
+    let Some(bytes) = read(storage, INDEX_PATH)? else {
+        return Ok(None);
+    };
+    let value: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| EntrypointError::Storage(format!("invalid index {INDEX_PATH}: {e}")))?;
+
+    let index = value["next_id"].as_u64().and_then(|next_id| {
+        let proofs = value["proofs"]
+            .as_array()?
+            .iter()
+            .map(ProofEntry::from_value)
+            .collect::<Option<_>>()?;
+        Some(Index { next_id, proofs })
+    });
+
+    index
+        .map(Some)
+        .ok_or_else(|| EntrypointError::Storage(format!("invalid index {INDEX_PATH}")))
+}
+
+fn write_index(storage: &mut impl Storage, index: &Index) -> Result<()> {
+    let proofs: Vec<Value> = index.proofs.iter().map(ProofEntry::to_value).collect();
+    let value = json!({ "next_id": index.next_id, "proofs": proofs });
+    let bytes = serde_json::to_vec(&value)
+        .map_err(|e| EntrypointError::Storage(format!("failed to serialize the index: {e}")))?;
+
+    write(storage, INDEX_PATH, &bytes)
+}
+
+fn read(storage: &mut impl Storage, path: &str) -> Result<Option<Vec<u8>>> {
+    storage
+        .read(path)
+        .map_err(|e| EntrypointError::Storage(format!("failed to read {path}: {e}")))
+}
+
+fn write(storage: &mut impl Storage, path: &str, bytes: &[u8]) -> Result<()> {
+    storage
+        .write(path, bytes)
+        .map_err(|e| EntrypointError::Storage(format!("failed to write {path}: {e}")))
+}
+
+fn optional_str<'a>(payload: &'a Value, key: &str, field: &'static str) -> Result<Option<&'a str>> {
+    match &payload[key] {
+        Value::Null => Ok(None),
+        Value::String(value) => Ok(Some(value)),
+        _ => Err(EntrypointError::InvalidField {
+            field,
+            expected: "a string",
+        }),
+    }
+}
+
+fn optional_u64(payload: &Value, key: &str, field: &'static str) -> Result<Option<u64>> {
+    match &payload[key] {
+        Value::Null => Ok(None),
+        value => value
+            .as_u64()
+            .map(Some)
+            .ok_or(EntrypointError::InvalidField {
+                field,
+                expected: "an unsigned integer",
+            }),
+    }
+}
+
+fn optional_bool(payload: &Value, key: &str, field: &'static str) -> Result<Option<bool>> {
+    match &payload[key] {
+        Value::Null => Ok(None),
+        value => value
+            .as_bool()
+            .map(Some)
+            .ok_or(EntrypointError::InvalidField {
+                field,
+                expected: "a boolean",
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStorage(BTreeMap<String, Vec<u8>>);
+
+    impl Storage for MemoryStorage {
+        fn read(&mut self, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.get(path).filter(|bytes| !bytes.is_empty()).cloned())
+        }
+
+        fn write(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+            self.0.insert(path.to_string(), bytes.to_vec());
+            Ok(())
+        }
+    }
+
+    /// Fails to read the index, as the service may on a transient error.
+    struct FailingStorage(MemoryStorage);
+
+    impl Storage for FailingStorage {
+        fn read(&mut self, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+            if path == INDEX_PATH {
+                anyhow::bail!("connection reset");
+            }
+            self.0.read(path)
+        }
+
+        fn write(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+            self.0.write(path, bytes)
+        }
+    }
+
+    fn delivered(value: u64, payload: Value) -> Value {
+        json!({
+            "args": { "value": value },
+            "log": [],
+            "payload": payload,
+            "proof": "cHJvb2Y=",
+            "success": true,
+        })
+    }
+
+    fn store(storage: &mut MemoryStorage, value: u64, timestamp: u64) -> Value {
+        let document = delivered(
+            value,
+            json!({ "cmd": "store", "request_id": format!("req-{value}"), "timestamp": timestamp }),
+        );
+        handle(storage, &document).expect("store should succeed")
+    }
+
+    fn command(storage: &mut MemoryStorage, payload: Value) -> Result<Value> {
+        handle(storage, &json!({ "payload": payload }))
+    }
+
+    #[test]
+    fn test_store_indexes_metadata() {
+        let mut storage = MemoryStorage::default();
+
+        let entry = store(&mut storage, 42, 1_700_000_000);
+        assert_eq!(
+            entry,
+            json!({
+                "id": 1,
+                "request_id": "req-42",
+                "path": "/var/share/proofs/00000001.bin",
+                "args": { "value": 42 },
+                "timestamp": 1_700_000_000u64,
+            })
+        );
+
+        let stored: Value =
+            serde_json::from_slice(&storage.0["/var/share/proofs/00000001.bin"]).unwrap();
+        assert_eq!(stored["proof"], "cHJvb2Y=");
+    }
+
+    #[test]
+    fn test_store_keeps_the_requested_path() {
+        let mut storage = MemoryStorage::default();
+        let document = delivered(1, json!({ "cmd": "store", "path": "/var/share/proof.bin" }));
+
+        let entry = handle(&mut storage, &document).unwrap();
+        assert_eq!(entry["path"], "/var/share/proof.bin");
+        assert_eq!(entry["request_id"], "proof-1");
+        assert_eq!(entry["timestamp"], Value::Null);
+        assert!(storage.0.contains_key("/var/share/proof.bin"));
+    }
+
+    #[test]
+    fn test_store_rejects_failed_proofs() {
+        let mut storage = MemoryStorage::default();
+        let mut document = delivered(1, json!({ "cmd": "store" }));
+        document["success"] = json!(false);
+
+        let error = handle(&mut storage, &document).unwrap_err();
+        assert_eq!(error.code(), "proof-failed");
+        assert!(storage.0.is_empty(), "nothing should be stored");
+    }
+
+    #[test]
+    fn test_list_and_latest() {
+        let mut storage = MemoryStorage::default();
+        for value in 1..=3 {
+            store(&mut storage, value, value * 100);
+        }
+
+        let listed = command(&mut storage, json!({ "cmd": "list", "limit": 2 })).unwrap();
+        let ids: Vec<u64> = listed["proofs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![3, 2]);
+
+        let latest = command(&mut storage, json!({ "cmd": "latest" })).unwrap();
+        assert_eq!(latest["entry"]["request_id"], "req-3");
+        assert_eq!(latest["proof"]["args"]["value"], 3);
+    }
+
+    #[test]
+    fn test_delete_old_proofs() {
+        let mut storage = MemoryStorage::default();
+        for value in 1..=4 {
+            store(&mut storage, value, value * 100);
         }
 
-        _ => panic!("unknown entrypoint command"),
+        let deleted = command(&mut storage, json!({ "cmd": "delete", "before": 200 })).unwrap();
+        assert_eq!(deleted["deleted"], json!([1]));
+
+        let deleted = command(&mut storage, json!({ "cmd": "delete", "keep": 1 })).unwrap();
+        assert_eq!(deleted["deleted"], json!([2, 3]));
+
+        let listed = command(&mut storage, json!({ "cmd": "list" })).unwrap();
+        assert_eq!(listed["proofs"].as_array().unwrap().len(), 1);
+        assert!(storage.0["/var/share/proofs/00000002.bin"].is_empty());
+
+        // Ids keep increasing after a deletion
+        assert_eq!(store(&mut storage, 5, 500)["id"], 5);
     }
 
-    Ok(args)
+    #[test]
+    fn test_ids_are_not_reused_once_every_proof_is_deleted() {
+        let mut storage = MemoryStorage::default();
+        store(&mut storage, 1, 100);
+        store(&mut storage, 2, 200);
+
+        let deleted = command(&mut storage, json!({ "cmd": "delete", "keep": 0 })).unwrap();
+        assert_eq!(deleted["deleted"], json!([1, 2]));
+        let listed = command(&mut storage, json!({ "cmd": "list" })).unwrap();
+        assert_eq!(listed["proofs"], json!([]));
+
+        let entry = store(&mut storage, 3, 300);
+        assert_eq!(entry["id"], 3);
+        assert_eq!(entry["request_id"], "req-3");
+        assert_eq!(entry["path"], "/var/share/proofs/00000003.bin");
+        assert!(storage.0["/var/share/proofs/00000001.bin"].is_empty());
+    }
+
+    #[test]
+    fn test_store_keeps_the_index_on_read_errors() {
+        let mut storage = MemoryStorage::default();
+        store(&mut storage, 1, 100);
+        let index = storage.0[INDEX_PATH].clone();
+
+        let mut failing = FailingStorage(storage);
+        let error = handle(&mut failing, &delivered(2, json!({ "cmd": "store" }))).unwrap_err();
+        assert_eq!(error.code(), "storage");
+        assert_eq!(failing.0 .0[INDEX_PATH], index);
+    }
+
+    #[test]
+    fn test_init_keeps_a_readable_index() {
+        let mut storage = MemoryStorage::default();
+        let result = command(&mut storage, json!({ "cmd": "init" })).unwrap();
+        assert_eq!(result, json!({ "next_id": 1, "proofs": 0 }));
+        assert!(storage.0.contains_key(INDEX_PATH));
+
+        store(&mut storage, 1, 100);
+        let result = command(&mut storage, json!({ "cmd": "init", "force": true })).unwrap();
+        assert_eq!(result, json!({ "next_id": 2, "proofs": 1 }));
+    }
+
+    #[test]
+    fn test_init_only_overwrites_unreadable_index_when_forced() {
+        let mut storage = MemoryStorage::default();
+        store(&mut storage, 1, 100);
+        let index = storage.0[INDEX_PATH].clone();
+
+        let mut failing = FailingStorage(storage);
+        let error = handle(&mut failing, &json!({ "payload": { "cmd": "init" } })).unwrap_err();
+        assert_eq!(error.code(), "storage");
+        assert_eq!(failing.0 .0[INDEX_PATH], index);
+
+        let payload = json!({ "payload": { "cmd": "init", "force": true } });
+        handle(&mut failing, &payload).unwrap();
+        assert_ne!(failing.0 .0[INDEX_PATH], index);
+    }
+
+    #[test]
+    fn test_errors_are_structured() {
+        let mut storage = MemoryStorage::default();
+
+        let error = command(&mut storage, json!({ "cmd": "latest" })).unwrap_err();
+        assert_eq!(error.code(), "not-found");
+
+        let error = command(&mut storage, json!({ "cmd": "compact" })).unwrap_err();
+        assert_eq!(
+            error,
+            EntrypointError::UnknownCommand("compact".to_string())
+        );
+
+        let error = handle(&mut storage, &json!({})).unwrap_err();
+        assert_eq!(error, EntrypointError::MissingField("payload.cmd"));
+
+        let error = command(&mut storage, json!({ "cmd": "delete" })).unwrap_err();
+        assert_eq!(error.code(), "missing-field");
+
+        let error = command(&mut storage, json!({ "cmd": "list", "limit": "ten" })).unwrap_err();
+        assert_eq!(
+            error.to_value(),
+            json!({
+                "code": "invalid-field",
+                "message": "field payload.limit should be an unsigned integer",
+            })
+        );
+    }
 }
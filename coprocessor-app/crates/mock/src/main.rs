@@ -0,0 +1,168 @@
+//! Local stand-in for the Valence co-processor service.
+//!
+//! Serves the routes `CoprocessorClient` uses to fetch verification keys and request proofs.
+//! Proofs are produced by running the controller and the circuit natively and carry empty
+//! proof bytes, so they only verify against a mock SP1 verifier.
+
+mod proof;
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use clap::Parser;
+use log::info;
+use serde_json::{json, Value};
+
+#[derive(Parser)]
+#[command(name = "valence-coprocessor-mock", about)]
+struct Cli {
+    /// Address to listen on, the public coprocessor uses port 37281
+    #[arg(long, default_value = "127.0.0.1:37281")]
+    listen: SocketAddr,
+}
+
+/// Files written by the entrypoint, per controller and path.
+type Storage = Arc<Mutex<HashMap<(String, String), Vec<u8>>>>;
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn stats() -> Json<Value> {
+    Json(json!({ "mock": true }))
+}
+
+async fn domain_vk() -> Json<Value> {
+    Json(json!({ "base64": STANDARD.encode(proof::domain_vk()) }))
+}
+
+async fn controller_vk(Path(controller): Path<String>) -> Json<Value> {
+    Json(json!({ "base64": STANDARD.encode(proof::program_vk(&controller)) }))
+}
+
+/// Proves the request right away and delivers the result to the controller entrypoint
+/// logic: the request document, completed with the proofs, is stored and indexed according
+/// to `payload`.
+async fn prove(
+    State(storage): State<Storage>,
+    Path(controller): Path<String>,
+    Json(request): Json<Value>,
+) -> Result<Json<Value>, ApiError> {
+    let args = request.get("args").cloned().unwrap_or(Value::Null);
+    info!("proof request for {controller} with arguments {args}");
+
+    let proof =
+        proof::prove(&args).map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{e:#}")))?;
+
+    let payload = request.get("payload").cloned().unwrap_or(Value::Null);
+    let document = json!({
+        "args": args,
+        "log": [format!("received a proof request with arguments {args}")],
+        "payload": payload,
+        "proof": proof.program,
+        "domain": proof.domain,
+        "success": true,
+    });
+
+    if payload["cmd"].is_string() {
+        let mut files = storage.lock().expect("storage lock poisoned");
+        valence_coprocessor_app_controller::handle(
+            &mut ControllerFiles {
+                files: &mut *files,
+                controller,
+            },
+            &document,
+        )
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+
+    Ok(Json(document))
+}
+
+/// Files of one controller, as seen by its entrypoint.
+struct ControllerFiles<'a> {
+    files: &'a mut HashMap<(String, String), Vec<u8>>,
+    controller: String,
+}
+
+impl valence_coprocessor_app_controller::Storage for ControllerFiles<'_> {
+    fn read(&mut self, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self
+            .files
+            .get(&(self.controller.clone(), path.to_lowercase()))
+            .filter(|bytes| !bytes.is_empty())
+            .cloned())
+    }
+
+    fn write(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        self.files.insert(
+            (self.controller.clone(), path.to_lowercase()),
+            bytes.to_vec(),
+        );
+        Ok(())
+    }
+}
+
+/// Reads a file stored by [`prove`]. Paths are case insensitive, like the FAT-16 storage of the service.
+async fn storage_file(
+    State(storage): State<Storage>,
+    Path(controller): Path<String>,
+    Json(request): Json<Value>,
+) -> Result<Json<Value>, ApiError> {
+    let path = request["path"]
+        .as_str()
+        .ok_or_else(|| ApiError(StatusCode::BAD_REQUEST, "missing path".to_string()))?
+        .to_lowercase();
+
+    let storage = storage.lock().expect("storage lock poisoned");
+    let bytes = storage
+        .get(&(controller, path.clone()))
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("no file at {path}")))?;
+
+    Ok(Json(json!({ "data": STANDARD.encode(bytes) })))
+}
+
+fn router() -> Router {
+    Router::new()
+        .route("/api/stats", get(stats))
+        .route("/api/circuit/vk", get(domain_vk))
+        .route("/api/registry/controller/{controller}/vk", get(controller_vk))
+        .route("/api/registry/controller/{controller}/prove", post(prove))
+        .route(
+            "/api/registry/controller/{controller}/storage/fs",
+            post(storage_file),
+        )
+        .with_state(Storage::default())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let cli = Cli::parse();
+    let listener = tokio::net::TcpListener::bind(cli.listen).await?;
+    info!("mock coprocessor listening on http://{}", cli.listen);
+    info!(
+        "domain VK 0x{}",
+        alloy_primitives::hex::encode(proof::domain_vk())
+    );
+
+    axum::serve(listener, router()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use alloy_primitives::keccak256;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::Value;
+
+/// Coprocessor root opening the public inputs of every mock proof.
+pub const MOCK_ROOT: [u8; 32] = [0; 32];
+
+/// Verification key of the mock domain circuit.
+pub fn domain_vk() -> [u8; 32] {
+    keccak256(b"valence-coprocessor-mock/domain").0
+}
+
+/// Verification key of a controller. It only has to be stable for a given controller id.
+pub fn program_vk(controller: &str) -> [u8; 32] {
+    keccak256(format!("valence-coprocessor-mock/controller/{controller}")).0
+}
+
+/// Program and domain proofs of a request, encoded as the coprocessor delivers them.
+pub struct MockProof {
+    pub program: String,
+    pub domain: String,
+}
+
+/// Runs the controller witnesses and the circuit natively and wraps the circuit output
+/// into proofs with empty proof bytes, which only a mock SP1 verifier accepts.
+pub fn prove(args: &Value) -> anyhow::Result<MockProof> {
+    let witnesses = valence_coprocessor_app_controller::witnesses(args)?;
+
+    // The circuit asserts its statements by panicking
+    let output = panic::catch_unwind(AssertUnwindSafe(|| {
+        valence_coprocessor_app_circuit::circuit(witnesses)
+    }))
+    .map_err(|e| {
+        let reason = e
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| e.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown reason");
+        anyhow::anyhow!("circuit rejected the witnesses: {reason}")
+    })?;
+
+    let mut program_inputs = MOCK_ROOT.to_vec();
+    program_inputs.extend_from_slice(&output);
+
+    Ok(MockProof {
+        program: encode(&[], &program_inputs),
+        domain: encode(&[], &MOCK_ROOT),
+    })
+}
+
+/// Encodes a proof like the coprocessor does: the base64 proof bytes and public inputs as
+/// two consecutive msgpack strings, the whole being base64 encoded again.
+pub fn encode(proof: &[u8], inputs: &[u8]) -> String {
+    let mut bytes = Vec::new();
+    write_msgpack_str(&mut bytes, &STANDARD.encode(proof));
+    write_msgpack_str(&mut bytes, &STANDARD.encode(inputs));
+
+    STANDARD.encode(bytes)
+}
+
+fn write_msgpack_str(buf: &mut Vec<u8>, s: &str) {
+    let len = s.len();
+    match len {
+        0..=31 => buf.push(0xa0 | len as u8),
+        32..=0xff => buf.extend_from_slice(&[0xd9, len as u8]),
+        0x100..=0xffff => {
+            buf.push(0xda);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdb);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    buf.extend_from_slice(s.as_bytes());
+}
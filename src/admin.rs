@@ -0,0 +1,361 @@
+use std::{collections::BTreeSet, fmt};
+
+use alloy::{
+    primitives::{Address, Bytes, TxHash},
+    providers::Provider,
+    rpc::types::{Filter, TransactionRequest},
+    sol_types::{SolEvent, SolValue},
+    transports::Transport,
+};
+use serde::{Deserialize, Serialize};
+use valence_domain_clients::{clients::ethereum::EthereumClient, evm::base_client::EvmBaseClient};
+
+use crate::{
+    error::{Error, Result},
+    indexer::get_logs_batched,
+    types::{
+        processor_messages::{pause_message, resume_message},
+        sol_types::{
+            processor_contract::LiteProcessor, Authorization, BaseAccount, Forwarder,
+            SP1VerificationGateway,
+        },
+    },
+};
+
+/// Addresses of every contract that makes up the program.
+///
+/// Only the Authorization and Forwarder addresses need to be known up front,
+/// everything else is read back from their on-chain configuration.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProgramContracts {
+    pub authorization: Address,
+    pub processor: Address,
+    pub forwarder: Address,
+    pub verification_gateway: Address,
+    pub send_account: Address,
+    pub deposit_account: Address,
+}
+
+impl ProgramContracts {
+    pub async fn discover<T, P>(rp: &P, authorization: Address, forwarder: Address) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let authorization_contract = Authorization::new(authorization, rp);
+        let processor = authorization_contract.processor().call().await?._0;
+        let verification_gateway = authorization_contract
+            .verificationGateway()
+            .call()
+            .await?
+            ._0;
+
+        let config = Forwarder::new(forwarder, rp).config().call().await?;
+
+        Ok(Self {
+            authorization,
+            processor,
+            forwarder,
+            verification_gateway,
+            send_account: config.inputAccount,
+            deposit_account: config.outputAccount,
+        })
+    }
+
+    pub fn address_of(&self, contract: OwnableContract) -> Address {
+        match contract {
+            OwnableContract::SendAccount => self.send_account,
+            OwnableContract::DepositAccount => self.deposit_account,
+            OwnableContract::Processor => self.processor,
+            OwnableContract::Authorization => self.authorization,
+            OwnableContract::Forwarder => self.forwarder,
+            OwnableContract::VerificationGateway => self.verification_gateway,
+        }
+    }
+}
+
+/// Every contract of the program that inherits from `Ownable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnableContract {
+    SendAccount,
+    DepositAccount,
+    Processor,
+    Authorization,
+    Forwarder,
+    VerificationGateway,
+}
+
+impl OwnableContract {
+    pub const ALL: [OwnableContract; 6] = [
+        OwnableContract::SendAccount,
+        OwnableContract::DepositAccount,
+        OwnableContract::Processor,
+        OwnableContract::Authorization,
+        OwnableContract::Forwarder,
+        OwnableContract::VerificationGateway,
+    ];
+
+    pub async fn owner<T, P>(&self, rp: &P, address: Address) -> Result<Address>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let owner = match self {
+            OwnableContract::SendAccount | OwnableContract::DepositAccount => {
+                BaseAccount::new(address, rp).owner().call().await?._0
+            }
+            OwnableContract::Processor => LiteProcessor::new(address, rp).owner().call().await?._0,
+            OwnableContract::Authorization => {
+                Authorization::new(address, rp).owner().call().await?._0
+            }
+            OwnableContract::Forwarder => Forwarder::new(address, rp).owner().call().await?._0,
+            OwnableContract::VerificationGateway => {
+                SP1VerificationGateway::new(address, rp)
+                    .owner()
+                    .call()
+                    .await?
+                    ._0
+            }
+        };
+
+        Ok(owner)
+    }
+
+    pub fn transfer_ownership_tx<T, P>(
+        &self,
+        rp: &P,
+        address: Address,
+        new_owner: Address,
+    ) -> TransactionRequest
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        match self {
+            OwnableContract::SendAccount | OwnableContract::DepositAccount => {
+                BaseAccount::new(address, rp)
+                    .transferOwnership(new_owner)
+                    .into_transaction_request()
+            }
+            OwnableContract::Processor => LiteProcessor::new(address, rp)
+                .transferOwnership(new_owner)
+                .into_transaction_request(),
+            OwnableContract::Authorization => Authorization::new(address, rp)
+                .transferOwnership(new_owner)
+                .into_transaction_request(),
+            OwnableContract::Forwarder => Forwarder::new(address, rp)
+                .transferOwnership(new_owner)
+                .into_transaction_request(),
+            OwnableContract::VerificationGateway => SP1VerificationGateway::new(address, rp)
+                .transferOwnership(new_owner)
+                .into_transaction_request(),
+        }
+    }
+}
+
+impl fmt::Display for OwnableContract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OwnableContract::SendAccount => "Send account",
+            OwnableContract::DepositAccount => "Deposit account",
+            OwnableContract::Processor => "Processor",
+            OwnableContract::Authorization => "Authorization",
+            OwnableContract::Forwarder => "Forwarder",
+            OwnableContract::VerificationGateway => "Verification Gateway",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Snapshot of every privileged role in the program.
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlReport {
+    pub owners: Vec<(OwnableContract, Address, Address)>,
+    pub authorization_admins: Vec<Address>,
+    pub processor_authorized_addresses: Vec<Address>,
+    pub forwarder_processor: Address,
+}
+
+impl ControlReport {
+    /// Reads the owners, and replays the admin events from `from_block` on, see
+    /// [`DeploymentManifest::first_block`](crate::manifest::DeploymentManifest::first_block).
+    pub async fn load<T, P>(rp: &P, contracts: &ProgramContracts, from_block: u64) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let mut owners = vec![];
+        for contract in OwnableContract::ALL {
+            let address = contracts.address_of(contract);
+            owners.push((contract, address, contract.owner(rp, address).await?));
+        }
+
+        // Admins and authorized addresses are stored in mappings, so we rebuild the sets from events
+        let authorization_admins = replay_address_set(
+            rp,
+            contracts.authorization,
+            from_block,
+            |event: &Authorization::AdminAddressAdded| event.admin,
+            |event: &Authorization::AdminAddressRemoved| event.admin,
+        )
+        .await?;
+
+        let processor_authorized_addresses = replay_address_set(
+            rp,
+            contracts.processor,
+            from_block,
+            |event: &LiteProcessor::AuthorizedAddressAdded| event.addr,
+            |event: &LiteProcessor::AuthorizedAddressRemoved| event.addr,
+        )
+        .await?;
+
+        let forwarder_processor = Forwarder::new(contracts.forwarder, rp)
+            .processor()
+            .call()
+            .await?
+            ._0;
+
+        Ok(Self {
+            owners,
+            authorization_admins,
+            processor_authorized_addresses,
+            forwarder_processor,
+        })
+    }
+}
+
+impl fmt::Display for ControlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Owners:")?;
+        for (contract, address, owner) in &self.owners {
+            writeln!(f, "  {contract:<22} {address} owned by {owner}")?;
+        }
+
+        writeln!(f, "Authorization admins:")?;
+        if self.authorization_admins.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for admin in &self.authorization_admins {
+            writeln!(f, "  {admin}")?;
+        }
+
+        writeln!(f, "Processor authorized addresses:")?;
+        if self.processor_authorized_addresses.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for address in &self.processor_authorized_addresses {
+            writeln!(f, "  {address}")?;
+        }
+
+        write!(f, "Forwarder processor: {}", self.forwarder_processor)
+    }
+}
+
+/// Pause or Resume processor message sent through the Authorization contract, which only
+/// accepts them from its owner or an admin.
+pub fn set_processor_paused_tx<T, P>(
+    rp: &P,
+    contracts: &ProgramContracts,
+    pause: bool,
+) -> TransactionRequest
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let message = if pause {
+        pause_message()
+    } else {
+        resume_message()
+    };
+
+    // The label is only checked for SendMsgs, admin messages don't need an authorization
+    Authorization::new(contracts.authorization, rp)
+        .sendProcessorMessage(String::new(), Bytes::from(message.abi_encode()))
+        .into_transaction_request()
+}
+
+/// Pauses or resumes the processor by sending a Pause/Resume processor message through the
+/// Authorization contract, which only accepts them from its owner or an admin.
+///
+/// Returns the hash of the transaction that changed the state, or `None` if the processor
+/// was already in the requested state. The new state is confirmed both from the emitted
+/// `ProcessorWasPaused`/`ProcessorWasResumed` event and by reading `paused()` back.
+pub async fn set_processor_paused<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    contracts: &ProgramContracts,
+    pause: bool,
+) -> Result<Option<TxHash>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let processor = LiteProcessor::new(contracts.processor, rp);
+    if processor.paused().call().await?._0 == pause {
+        return Ok(None);
+    }
+
+    let tx = set_processor_paused_tx(rp, contracts, pause);
+    let receipt = eth_client.sign_and_send(tx).await.map_err(Error::signing)?;
+
+    let emitted = receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.address() == contracts.processor)
+        .any(|log| {
+            if pause {
+                log.log_decode::<LiteProcessor::ProcessorWasPaused>()
+                    .is_ok()
+            } else {
+                log.log_decode::<LiteProcessor::ProcessorWasResumed>()
+                    .is_ok()
+            }
+        });
+    if !emitted {
+        return Err(Error::UnexpectedState(format!(
+            "transaction {} did not emit the expected processor event",
+            receipt.transaction_hash
+        )));
+    }
+
+    let paused = processor.paused().call().await?._0;
+    if paused != pause {
+        return Err(Error::UnexpectedState(format!(
+            "processor paused state is still {paused}"
+        )));
+    }
+
+    Ok(Some(receipt.transaction_hash))
+}
+
+/// Rebuilds the current members of an on-chain address set by replaying its add/remove events.
+async fn replay_address_set<T, P, Added, Removed>(
+    rp: &P,
+    address: Address,
+    from_block: u64,
+    added: impl Fn(&Added) -> Address,
+    removed: impl Fn(&Removed) -> Address,
+) -> Result<Vec<Address>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+    Added: SolEvent,
+    Removed: SolEvent,
+{
+    let filter = Filter::new()
+        .address(address)
+        .event_signature(vec![Added::SIGNATURE_HASH, Removed::SIGNATURE_HASH]);
+
+    let mut members = BTreeSet::new();
+    for log in get_logs_batched(rp, &filter, from_block).await? {
+        if let Ok(event) = log.log_decode::<Added>() {
+            members.insert(added(&event.inner.data));
+        } else if let Ok(event) = log.log_decode::<Removed>() {
+            members.remove(&removed(&event.inner.data));
+        }
+    }
+
+    Ok(members.into_iter().collect())
+}
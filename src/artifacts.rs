@@ -0,0 +1,515 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    primitives::{keccak256, Address, Bytes, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    deploy::Check,
+    error::{Error, Result},
+    manifest::DeploymentManifest,
+};
+
+/// Directory of the vendored contract artifacts the bindings are generated from, resolved at
+/// build time so the commands work from any directory.
+pub const ARTIFACTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/contracts");
+
+/// File, inside the artifacts directory, recording where every artifact comes from.
+pub const ARTIFACT_MANIFEST: &str = "artifacts.json";
+
+/// Contracts vendored as `<name>.sol/<name>.json`.
+pub const CONTRACTS: [&str; 7] = [
+    "Authorization",
+    "BaseAccount",
+    "ERC1967Proxy",
+    "Forwarder",
+    "LiteProcessor",
+    "MockERC20",
+    "SP1VerificationGateway",
+];
+
+pub fn artifact_path(dir: &Path, contract: &str) -> PathBuf {
+    dir.join(format!("{contract}.sol"))
+        .join(format!("{contract}.json"))
+}
+
+/// A Foundry build artifact.
+pub struct Artifact {
+    pub contract: String,
+    json: Value,
+}
+
+impl Artifact {
+    pub fn load(dir: &Path, contract: &str) -> Result<Self> {
+        let path = artifact_path(dir, contract);
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            Error::Config(format!("failed to read artifact {}: {e}", path.display()))
+        })?;
+        let json = serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("invalid artifact {}: {e}", path.display())))?;
+
+        Ok(Self {
+            contract: contract.to_string(),
+            json,
+        })
+    }
+
+    fn bytes(&self, field: &str) -> Result<Bytes> {
+        self.json[field]["object"]
+            .as_str()
+            .ok_or_else(|| Error::Config(format!("{} artifact has no {field}", self.contract)))?
+            .parse()
+            .map_err(|e| {
+                Error::Config(format!(
+                    "invalid {field} in {} artifact: {e}",
+                    self.contract
+                ))
+            })
+    }
+
+    pub fn bytecode(&self) -> Result<Bytes> {
+        self.bytes("bytecode")
+    }
+
+    pub fn deployed_bytecode(&self) -> Result<Bytes> {
+        self.bytes("deployedBytecode")
+    }
+
+    /// Byte ranges of the runtime code filled with immutables at deployment.
+    fn immutable_ranges(&self) -> Vec<(usize, usize)> {
+        self.json["deployedBytecode"]["immutableReferences"]
+            .as_object()
+            .into_iter()
+            .flat_map(|references| references.values())
+            .flat_map(|ranges| ranges.as_array().into_iter().flatten())
+            .filter_map(|range| {
+                Some((
+                    range["start"].as_u64()? as usize,
+                    range["length"].as_u64()? as usize,
+                ))
+            })
+            .collect()
+    }
+
+    fn metadata(&self) -> Value {
+        match &self.json["metadata"] {
+            Value::String(raw) => serde_json::from_str(raw).unwrap_or_default(),
+            metadata => metadata.clone(),
+        }
+    }
+
+    fn abi(&self) -> &[Value] {
+        self.json["abi"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Origin and content hashes of a vendored artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub contract: String,
+    /// Source file the contract was compiled from, relative to the Valence contracts repository.
+    pub source: String,
+    pub compiler: String,
+    pub evm_version: String,
+    pub optimizer_runs: Option<u64>,
+    pub via_ir: bool,
+    /// Versioned dependencies the contract was compiled with, e.g. `@openzeppelin-contracts-5.2.0`.
+    pub dependencies: Vec<String>,
+    pub bytecode_hash: B256,
+    pub deployed_bytecode_hash: B256,
+}
+
+impl ArtifactRecord {
+    pub fn from_artifact(artifact: &Artifact) -> Result<Self> {
+        let metadata = artifact.metadata();
+        let settings = &metadata["settings"];
+
+        let source = settings["compilationTarget"]
+            .as_object()
+            .and_then(|target| target.keys().next().cloned())
+            .unwrap_or_default();
+        let dependencies: BTreeSet<String> = metadata["sources"]
+            .as_object()
+            .into_iter()
+            .flat_map(|sources| sources.keys())
+            .filter_map(|path| path.strip_prefix("dependencies/"))
+            .filter_map(|path| path.split('/').next())
+            .map(str::to_string)
+            .collect();
+
+        Ok(Self {
+            contract: artifact.contract.clone(),
+            source,
+            compiler: metadata["compiler"]["version"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            evm_version: settings["evmVersion"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            optimizer_runs: settings["optimizer"]["enabled"]
+                .as_bool()
+                .unwrap_or_default()
+                .then(|| settings["optimizer"]["runs"].as_u64())
+                .flatten(),
+            via_ir: settings["viaIR"].as_bool().unwrap_or_default(),
+            dependencies: dependencies.into_iter().collect(),
+            bytecode_hash: keccak256(artifact.bytecode()?),
+            deployed_bytecode_hash: keccak256(artifact.deployed_bytecode()?),
+        })
+    }
+}
+
+/// Pins the vendored artifacts to the release they were built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub repository: String,
+    /// Release or commit of the repository, `None` only for manifests pinned before it was
+    /// required.
+    pub release: Option<String>,
+    pub artifacts: Vec<ArtifactRecord>,
+}
+
+impl ArtifactManifest {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(ARTIFACT_MANIFEST);
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            Error::Config(format!(
+                "failed to read artifact manifest {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        serde_json::from_str(&contents).map_err(|e| {
+            Error::Config(format!("invalid artifact manifest {}: {e}", path.display()))
+        })
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(ARTIFACT_MANIFEST);
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("failed to serialize artifact manifest: {e}")))?;
+
+        fs::write(&path, contents + "\n").map_err(|e| {
+            Error::Config(format!(
+                "failed to write artifact manifest {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Records every artifact currently in `dir`.
+    pub fn pin(dir: &Path, repository: String, release: String) -> Result<Self> {
+        let artifacts = CONTRACTS
+            .iter()
+            .map(|contract| ArtifactRecord::from_artifact(&Artifact::load(dir, contract)?))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            repository,
+            release: Some(release),
+            artifacts,
+        })
+    }
+
+    /// Checks that the artifacts in `dir` are the ones pinned in the manifest.
+    pub fn check(&self, dir: &Path) -> Result<Vec<Check>> {
+        let mut checks = Vec::new();
+        for record in &self.artifacts {
+            let current = ArtifactRecord::from_artifact(&Artifact::load(dir, &record.contract)?)?;
+
+            checks.push(Check::expect(
+                format!("{} bytecode", record.contract),
+                current.bytecode_hash,
+                record.bytecode_hash,
+            ));
+            checks.push(Check::expect(
+                format!("{} runtime bytecode", record.contract),
+                current.deployed_bytecode_hash,
+                record.deployed_bytecode_hash,
+            ));
+        }
+
+        Ok(checks)
+    }
+}
+
+/// Clears the immutable ranges, whose content depends on the deployment.
+fn mask_immutables(code: &[u8], ranges: &[(usize, usize)]) -> Vec<u8> {
+    let mut code = code.to_vec();
+    for &(start, length) in ranges {
+        if let Some(range) = code.get_mut(start..start + length) {
+            range.fill(0);
+        }
+    }
+
+    code
+}
+
+/// Compares the runtime code of every contract in the deployment with the vendored artifacts.
+pub async fn verify_deployed_code<T, P>(
+    rp: &P,
+    manifest: &DeploymentManifest,
+    dir: &Path,
+) -> Result<Vec<Check>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let contracts = &manifest.contracts;
+    let deployed: [(&str, &str, Address); 8] = [
+        ("send account", "BaseAccount", contracts.send_account),
+        ("deposit account", "BaseAccount", contracts.deposit_account),
+        ("processor", "LiteProcessor", contracts.processor),
+        ("forwarder", "Forwarder", contracts.forwarder),
+        ("authorization", "Authorization", contracts.authorization),
+        (
+            "verification gateway proxy",
+            "ERC1967Proxy",
+            contracts.verification_gateway,
+        ),
+        (
+            "verification gateway implementation",
+            "SP1VerificationGateway",
+            manifest.verification_gateway_implementation,
+        ),
+        ("DEMO token", "MockERC20", manifest.token),
+    ];
+
+    let mut checks = Vec::new();
+    for (name, contract, address) in deployed {
+        let artifact = Artifact::load(dir, contract)?;
+        let ranges = artifact.immutable_ranges();
+
+        let expected = mask_immutables(&artifact.deployed_bytecode()?, &ranges);
+        let actual = mask_immutables(&rp.get_code_at(address).await?, &ranges);
+
+        checks.push(Check {
+            name: format!("{name} code"),
+            ok: actual == expected,
+            detail: if actual == expected {
+                format!("{address} runs {contract}")
+            } else if actual.is_empty() {
+                format!("no code at {address}")
+            } else {
+                format!(
+                    "{address} runs code {} instead of {contract} {}",
+                    keccak256(&actual),
+                    keccak256(&expected)
+                )
+            },
+        });
+    }
+
+    Ok(checks)
+}
+
+fn canonical_type(param: &Value) -> String {
+    let ty = param["type"].as_str().unwrap_or_default();
+    match ty.strip_prefix("tuple") {
+        Some(suffix) => {
+            let components: Vec<String> = param["components"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(canonical_type)
+                .collect();
+            format!("({}){suffix}", components.join(","))
+        }
+        None => ty.to_string(),
+    }
+}
+
+fn params(item: &Value, field: &str) -> String {
+    item[field]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(canonical_type)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// ABI entries keyed by kind and signature, e.g. `function transferOwnership(address)`,
+/// with what can change without changing the signature.
+fn abi_entries(abi: &[Value]) -> BTreeMap<String, String> {
+    abi.iter()
+        .map(|item| {
+            let kind = item["type"].as_str().unwrap_or_default();
+            let name = item["name"].as_str().unwrap_or_default();
+            let signature = format!("{kind} {name}({})", params(item, "inputs"));
+            let details = match kind {
+                "function" => format!(
+                    "{} returns ({})",
+                    item["stateMutability"].as_str().unwrap_or_default(),
+                    params(item, "outputs")
+                ),
+                "event" => {
+                    let indexed: Vec<String> = item["inputs"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|input| input["indexed"].as_bool().unwrap_or_default().to_string())
+                        .collect();
+                    format!("indexed [{}]", indexed.join(","))
+                }
+                _ => item["stateMutability"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+
+            (signature, details)
+        })
+        .collect()
+}
+
+/// ABI differences of one contract between two artifact releases.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AbiChanges {
+    pub contract: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Same signature, different outputs, mutability or indexed event fields.
+    pub changed: Vec<String>,
+    pub bytecode_changed: bool,
+}
+
+impl AbiChanges {
+    /// Removed or changed entries break the bindings generated from the artifacts.
+    pub fn is_breaking(&self) -> bool {
+        !self.removed.is_empty() || !self.changed.is_empty()
+    }
+}
+
+/// ABI compatibility of a new artifact release with the vendored one.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompatibilityReport {
+    pub contracts: Vec<AbiChanges>,
+    /// Contracts the program needs that the new release doesn't provide.
+    pub missing: Vec<String>,
+}
+
+impl CompatibilityReport {
+    pub fn compare(current_dir: &Path, new_dir: &Path) -> Result<Self> {
+        let mut contracts = Vec::new();
+        let mut missing = Vec::new();
+
+        for contract in CONTRACTS {
+            if !artifact_path(new_dir, contract).exists() {
+                missing.push(contract.to_string());
+                continue;
+            }
+
+            let current = Artifact::load(current_dir, contract)?;
+            let new = Artifact::load(new_dir, contract)?;
+            let before = abi_entries(current.abi());
+            let after = abi_entries(new.abi());
+
+            contracts.push(AbiChanges {
+                contract: contract.to_string(),
+                added: after
+                    .keys()
+                    .filter(|signature| !before.contains_key(*signature))
+                    .cloned()
+                    .collect(),
+                removed: before
+                    .keys()
+                    .filter(|signature| !after.contains_key(*signature))
+                    .cloned()
+                    .collect(),
+                changed: before
+                    .iter()
+                    .filter_map(|(signature, details)| {
+                        let new_details = after.get(signature)?;
+                        (new_details != details)
+                            .then(|| format!("{signature}: {details} -> {new_details}"))
+                    })
+                    .collect(),
+                bytecode_changed: current.bytecode()? != new.bytecode()?,
+            });
+        }
+
+        Ok(Self { contracts, missing })
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.missing.is_empty() && !self.contracts.iter().any(AbiChanges::is_breaking)
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for changes in &self.contracts {
+            let status = if changes.is_breaking() {
+                "BREAKING"
+            } else if !changes.added.is_empty() || changes.bytecode_changed {
+                "compatible"
+            } else {
+                "unchanged"
+            };
+            writeln!(f, "{}: {status}", changes.contract)?;
+
+            for signature in &changes.removed {
+                writeln!(f, "  - {signature}")?;
+            }
+            for change in &changes.changed {
+                writeln!(f, "  ~ {change}")?;
+            }
+            for signature in &changes.added {
+                writeln!(f, "  + {signature}")?;
+            }
+            if changes.bytecode_changed {
+                writeln!(f, "  bytecode changed")?;
+            }
+        }
+        for contract in &self.missing {
+            writeln!(f, "{contract}: missing from the new release")?;
+        }
+
+        write!(
+            f,
+            "{}",
+            if self.is_compatible() {
+                "The new artifacts are compatible with the bindings"
+            } else {
+                "The new artifacts break the bindings"
+            }
+        )
+    }
+}
+
+/// Copies the artifacts of a new release over the vendored ones and pins them.
+pub fn update(
+    current_dir: &Path,
+    new_dir: &Path,
+    repository: String,
+    release: String,
+) -> Result<ArtifactManifest> {
+    for contract in CONTRACTS {
+        let target = artifact_path(current_dir, contract);
+        fs::copy(artifact_path(new_dir, contract), &target).map_err(|e| {
+            Error::Config(format!(
+                "failed to update artifact {}: {e}",
+                target.display()
+            ))
+        })?;
+    }
+
+    let manifest = ArtifactManifest::pin(current_dir, repository, release)?;
+    manifest.save(current_dir)?;
+
+    Ok(manifest)
+}
@@ -0,0 +1,170 @@
+use std::{fmt, path::PathBuf, time::Duration};
+
+use alloy::providers::Provider;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::indexer::{
+    deployment_block, Forward, IndexedEvent, Indexer, DEFAULT_BATCH_SIZE,
+};
+use simple_program_demo::logging::{self, LogFormat};
+use simple_program_demo::manifest::{DeploymentManifest, MANIFEST_HELP};
+use simple_program_demo::signer::read_provider;
+
+#[derive(Parser)]
+#[command(
+    name = "program-indexer",
+    about = "Index the events of the Valence forwarding program into a local SQLite database"
+)]
+struct Cli {
+    /// Ethereum JSON-RPC endpoint
+    #[arg(long, global = true, default_value = "http://127.0.0.1:8545")]
+    rpc_url: String,
+
+    #[arg(long, global = true, default_value = "deployment.json", help = MANIFEST_HELP)]
+    manifest: PathBuf,
+
+    /// SQLite database holding the index, created if it doesn't exist
+    #[arg(long, global = true, default_value = "program-index.sqlite")]
+    db: PathBuf,
+
+    /// Print results as JSON on stdout, progress is still logged to stderr
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Format of the logs on stderr: text, or json for one object per line
+    #[arg(long, global = true, env = "VALENCE_DEMO_LOG_FORMAT", default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index the blocks since the last run, from the deployment of the program on a new index
+    Sync {
+        /// First block of a new index, found from the Authorization contract code when not set
+        #[arg(long)]
+        from_block: Option<u64>,
+        /// Blocks per eth_getLogs request
+        #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+        batch_size: u64,
+        /// Leave this many blocks below the chain head unindexed
+        #[arg(long, default_value_t = 0)]
+        confirmations: u64,
+        /// Keep following the chain head
+        #[arg(long)]
+        follow: bool,
+        /// Seconds between two syncs with --follow
+        #[arg(long, default_value_t = 12)]
+        interval: u64,
+    },
+    /// List every forward with the amount it moved and its result
+    Forwards,
+    /// List the indexed events
+    Events {
+        /// Only list events of this kind, e.g. callback-received or transfer
+        #[arg(long)]
+        kind: Option<String>,
+    },
+}
+
+/// Rows printed as a table, or as a JSON array.
+#[derive(Serialize)]
+#[serde(transparent)]
+struct Table<R> {
+    rows: Vec<R>,
+    #[serde(skip)]
+    header: String,
+}
+
+impl<R: fmt::Display> fmt::Display for Table<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header)?;
+        for row in &self.rows {
+            write!(f, "\n{row}")?;
+        }
+        Ok(())
+    }
+}
+
+fn print<T: Serialize + fmt::Display>(json: bool, value: &T) -> Result<()> {
+    if json {
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| Error::Config(format!("failed to serialize output: {e}")))?;
+        println!("{json}");
+    } else {
+        println!("{value}");
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    logging::init(cli.log_format)?;
+
+    let rp = read_provider(&cli.rpc_url)?;
+
+    let manifest = DeploymentManifest::load_or_discover(&cli.manifest, &rp)
+        .await
+        .step("loading deployment manifest")?;
+    let chain_id = rp.get_chain_id().await.step("querying chain id")?;
+    let mut indexer = Indexer::open(&cli.db, chain_id, manifest.contracts, manifest.token)?;
+
+    match cli.command {
+        Command::Sync {
+            from_block,
+            batch_size,
+            confirmations,
+            follow,
+            interval,
+        } => {
+            indexer.batch_size = batch_size.max(1);
+            indexer.confirmations = confirmations;
+
+            let start_block = match from_block.or(indexer.start_block()?) {
+                Some(block) => block,
+                None => {
+                    let block = deployment_block(&rp, manifest.contracts.authorization)
+                        .await
+                        .step("finding the deployment block, pass --from-block on nodes without historical state")?;
+                    log::info!("Program deployed at block {block}");
+                    block
+                }
+            };
+
+            loop {
+                let report = indexer.sync(&rp, start_block).await?;
+                print(cli.json, &report)?;
+
+                if !follow {
+                    return Ok(());
+                }
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+            }
+        }
+        Command::Forwards => print(
+            cli.json,
+            &Table::<Forward> {
+                rows: indexer.forwards()?,
+                header: format!(
+                    "{:>12} {:>10}  {:<20} {:>24}  {}",
+                    "Execution id", "Block", "Result", "Amount", "Transaction"
+                ),
+            },
+        ),
+        Command::Events { kind } => print(
+            cli.json,
+            &Table::<IndexedEvent> {
+                rows: indexer.events(kind.as_deref())?,
+                header: format!(
+                    "{:>10} {:>5}  {:<24} {}",
+                    "Block", "Log", "Kind", "Transaction"
+                ),
+            },
+        ),
+    }
+}
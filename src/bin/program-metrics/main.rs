@@ -0,0 +1,148 @@
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use alloy::primitives::Address;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use clap::Parser;
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::indexer::{deployment_block, DEFAULT_BATCH_SIZE};
+use simple_program_demo::logging::{self, LogFormat};
+use simple_program_demo::manifest::{DeploymentManifest, MANIFEST_HELP};
+use simple_program_demo::metrics::MetricsCollector;
+use simple_program_demo::signer::{read_provider, ReadProvider};
+use tokio::{net::TcpListener, sync::RwLock, time::MissedTickBehavior};
+
+#[derive(Parser)]
+#[command(
+    name = "program-metrics",
+    about = "Serve Prometheus metrics of the Valence forwarding program on /metrics"
+)]
+struct Cli {
+    /// Ethereum JSON-RPC endpoint
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    rpc_url: String,
+
+    #[arg(long, default_value = "deployment.json", help = MANIFEST_HELP)]
+    manifest: PathBuf,
+
+    /// Address the HTTP server listens on
+    #[arg(long, default_value = "127.0.0.1:9464")]
+    listen: SocketAddr,
+
+    /// Also export the balances of the program accounts in this token, can be repeated
+    #[arg(long = "token")]
+    tokens: Vec<Address>,
+
+    /// Proof timing written by `valence-demo activate --zk --proof-timing`
+    #[arg(long, env = "VALENCE_DEMO_PROOF_TIMING")]
+    proof_timing: Option<PathBuf>,
+
+    /// First block to count callbacks from, found from the Authorization contract code when not set
+    #[arg(long)]
+    from_block: Option<u64>,
+
+    /// Seconds between two collections, scrapes are answered with the last one
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    refresh_interval: u64,
+
+    /// Blocks per eth_getLogs request
+    #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+    batch_size: u64,
+
+    /// Format of the logs on stderr: text, or json for one object per line
+    #[arg(long, env = "VALENCE_DEMO_LOG_FORMAT", default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+}
+
+/// Last collected metrics in the exposition format, or why the last collection failed.
+type Snapshot = std::result::Result<String, String>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    logging::init(cli.log_format)?;
+
+    let rp = read_provider(&cli.rpc_url)?;
+
+    let manifest = DeploymentManifest::load_or_discover(&cli.manifest, &rp)
+        .await
+        .step("loading deployment manifest")?;
+    let start_block = match cli.from_block {
+        Some(block) => block,
+        None => deployment_block(&rp, manifest.contracts.authorization)
+            .await
+            .step(
+                "finding the deployment block, pass --from-block on nodes without historical state",
+            )?,
+    };
+
+    let mut collector =
+        MetricsCollector::new(&manifest, &cli.tokens, cli.proof_timing, start_block);
+    collector.batch_size = cli.batch_size;
+    // Count the past callbacks before the first scrape, which could time out otherwise
+    let first = collector.collect(&rp).await.step("collecting metrics")?;
+    // Scrapes read the last collection, so they never wait for the node nor for each other
+    let snapshot = Arc::new(RwLock::new(Ok(first.to_string())));
+    tokio::spawn(refresh(
+        collector,
+        rp,
+        snapshot.clone(),
+        Duration::from_secs(cli.refresh_interval),
+    ));
+
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .fallback(|| async { (StatusCode::NOT_FOUND, "Metrics are served on /metrics\n") })
+        .with_state(snapshot);
+
+    let listener = TcpListener::bind(cli.listen)
+        .await
+        .map_err(|e| Error::Config(format!("failed to listen on {}: {e}", cli.listen)))?;
+    log::info!("Serving metrics on http://{}/metrics", cli.listen);
+
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| Error::Config(format!("metrics server failed: {e}")))
+}
+
+/// Collects the metrics every `interval` into `snapshot`.
+async fn refresh(
+    mut collector: MetricsCollector,
+    rp: ReadProvider,
+    snapshot: Arc<RwLock<Snapshot>>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately, the metrics were just collected
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let collected = match collector.collect(&rp).await {
+            Ok(metrics) => Ok(metrics.to_string()),
+            Err(e) => {
+                log::warn!("Failed to collect metrics: {e}");
+                Err(e.to_string())
+            }
+        };
+        *snapshot.write().await = collected;
+    }
+}
+
+/// Serves the last collection on `GET /metrics`.
+async fn metrics(State(snapshot): State<Arc<RwLock<Snapshot>>>) -> Response {
+    match &*snapshot.read().await {
+        Ok(metrics) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            metrics.clone(),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, format!("{e}\n")).into_response(),
+    }
+}
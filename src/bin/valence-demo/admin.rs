@@ -0,0 +1,454 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use alloy::{
+    primitives::{Address, TxHash, B256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use clap::{Args, Subcommand, ValueEnum};
+use serde::Serialize;
+use simple_program_demo::admin::{
+    set_processor_paused, set_processor_paused_tx, ControlReport, OwnableContract,
+};
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::manifest::DeploymentManifest;
+use simple_program_demo::program::{
+    authorize_forward_tx, register_zk_registry_tx, FORWARD_LABEL, ZK_REGISTRY,
+};
+use simple_program_demo::safe::SafeBatch;
+use simple_program_demo::signer::ReadProvider;
+use simple_program_demo::types::sol_types::{
+    processor_contract::LiteProcessor, Authorization, BaseAccount,
+};
+use valence_domain_clients::{clients::ethereum::EthereumClient, evm::base_client::EvmBaseClient};
+
+use crate::Output;
+
+#[derive(Args)]
+pub struct AdminArgs {
+    #[command(subcommand)]
+    pub command: AdminCommand,
+    /// Append the call to this Safe Transaction Builder batch instead of sending it,
+    /// for contracts owned by a multisig. The file is created if it doesn't exist
+    #[arg(long, global = true, requires = "safe")]
+    pub safe_batch: Option<PathBuf>,
+    /// Safe the batch is executed from
+    #[arg(long, global = true, requires = "safe_batch")]
+    pub safe: Option<Address>,
+}
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Print who owns and administers every contract of the program
+    Report,
+    /// Allow an address to act as admin of the Authorization contract
+    AddAdmin { address: Address },
+    /// Revoke admin rights on the Authorization contract
+    RemoveAdmin { address: Address },
+    /// Check whether an address is an admin of the Authorization contract
+    IsAdmin { address: Address },
+    /// Point the Authorization contract to a different processor
+    UpdateProcessor { processor: Address },
+    /// Point the Authorization contract to a different verification gateway
+    UpdateVerificationGateway { verification_gateway: Address },
+    /// Allow an address to send messages to the processor
+    AddAuthorizedAddress { address: Address },
+    /// Revoke an address' access to the processor
+    RemoveAuthorizedAddress { address: Address },
+    /// Add the `forward` standard authorization, allowing anyone to trigger the forwarder
+    AuthorizeForward,
+    /// Allow an address to execute ZK messages proven against a program verification key
+    AddZkRegistry {
+        /// Program verification key, as returned by `SP1VerifyingKey::bytes32`
+        vk: B256,
+        /// Address allowed to submit the proofs, defaults to the signer
+        #[arg(long)]
+        executor: Option<Address>,
+    },
+    /// Approve a library on one of the program accounts
+    ApproveLibrary {
+        #[arg(value_enum)]
+        account: AccountArg,
+        library: Address,
+    },
+    /// Emergency stop: pause the processor through the Authorization contract
+    Pause,
+    /// Resume a paused processor through the Authorization contract
+    Resume,
+    /// Transfer ownership of one of the program contracts, after a confirmation prompt
+    ///
+    /// The contracts are plain `Ownable`: the transfer takes effect in one transaction and
+    /// the new owner doesn't have to accept it.
+    TransferOwnership {
+        #[arg(value_enum)]
+        contract: ContractArg,
+        new_owner: Address,
+        /// Skip the interactive confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ContractArg {
+    SendAccount,
+    DepositAccount,
+    Processor,
+    Authorization,
+    Forwarder,
+    VerificationGateway,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum AccountArg {
+    SendAccount,
+    DepositAccount,
+}
+
+impl From<AccountArg> for OwnableContract {
+    fn from(value: AccountArg) -> Self {
+        match value {
+            AccountArg::SendAccount => OwnableContract::SendAccount,
+            AccountArg::DepositAccount => OwnableContract::DepositAccount,
+        }
+    }
+}
+
+impl From<ContractArg> for OwnableContract {
+    fn from(value: ContractArg) -> Self {
+        match value {
+            ContractArg::SendAccount => OwnableContract::SendAccount,
+            ContractArg::DepositAccount => OwnableContract::DepositAccount,
+            ContractArg::Processor => OwnableContract::Processor,
+            ContractArg::Authorization => OwnableContract::Authorization,
+            ContractArg::Forwarder => OwnableContract::Forwarder,
+            ContractArg::VerificationGateway => OwnableContract::VerificationGateway,
+        }
+    }
+}
+
+/// Result of an admin command that changed, or checked, on-chain state.
+#[derive(Serialize)]
+pub struct AdminOutcome {
+    pub message: String,
+    pub tx_hash: Option<TxHash>,
+}
+
+impl AdminOutcome {
+    fn new(message: impl Into<String>, tx_hash: Option<TxHash>) -> Self {
+        Self {
+            message: message.into(),
+            tx_hash,
+        }
+    }
+}
+
+impl fmt::Display for AdminOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tx_hash {
+            Some(tx_hash) => write!(f, "{} (transaction {tx_hash})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// State-changing admin call, either sent by the signer or added to a Safe batch.
+struct AdminCall {
+    /// What the call does, used as the Safe batch description.
+    step: String,
+    tx: TransactionRequest,
+    /// Contract whose owner (or Authorization admin) is allowed to make the call.
+    contract: OwnableContract,
+}
+
+impl AdminCall {
+    fn new(step: impl Into<String>, tx: TransactionRequest, contract: OwnableContract) -> Self {
+        Self {
+            step: step.into(),
+            tx,
+            contract,
+        }
+    }
+}
+
+impl AdminArgs {
+    /// Whether the command sends a transaction. Reports and Safe batches only read the chain.
+    pub fn needs_signer(&self) -> bool {
+        self.safe_batch.is_none()
+            && !matches!(
+                self.command,
+                AdminCommand::Report | AdminCommand::IsAdmin { .. }
+            )
+    }
+}
+
+/// Runs an admin command, `eth_client` is only needed when [`AdminArgs::needs_signer`].
+pub async fn run(
+    args: AdminArgs,
+    rp: &ReadProvider,
+    eth_client: Option<&EthereumClient>,
+    manifest: &DeploymentManifest,
+    output: Output,
+) -> Result<()> {
+    let contracts = &manifest.contracts;
+    let authorization = Authorization::new(contracts.authorization, rp);
+    let processor = LiteProcessor::new(contracts.processor, rp);
+
+    match &args.command {
+        AdminCommand::Report => {
+            let from_block = manifest.first_block(rp).await?;
+            let report = ControlReport::load(rp, contracts, from_block).await?;
+            return output.print(&report);
+        }
+        AdminCommand::IsAdmin { address } => {
+            let is_admin = authorization.adminAddresses(*address).call().await?._0;
+            return output.print(&AdminOutcome::new(
+                format!("{address} is admin: {is_admin}"),
+                None,
+            ));
+        }
+        _ => {}
+    }
+
+    let my_address = eth_client.map(|eth_client| eth_client.signer().address());
+    // With a Safe batch, the Safe makes the calls and must hold the roles instead of the signer
+    let caller = args.safe.or(my_address).ok_or_else(|| {
+        Error::Config("admin calls need a signer, or --safe with --safe-batch".to_string())
+    })?;
+
+    let call = match &args.command {
+        AdminCommand::Report | AdminCommand::IsAdmin { .. } => unreachable!("handled above"),
+        AdminCommand::AddAdmin { address } => AdminCall::new(
+            format!("Add {address} as Authorization admin"),
+            authorization
+                .addAdminAddress(*address)
+                .into_transaction_request(),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::RemoveAdmin { address } => AdminCall::new(
+            format!("Remove {address} as Authorization admin"),
+            authorization
+                .removeAdminAddress(*address)
+                .into_transaction_request(),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::UpdateProcessor {
+            processor: new_processor,
+        } => AdminCall::new(
+            format!("Point Authorization to processor {new_processor}"),
+            authorization
+                .updateProcessor(*new_processor)
+                .into_transaction_request(),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::UpdateVerificationGateway {
+            verification_gateway,
+        } => AdminCall::new(
+            format!("Point Authorization to verification gateway {verification_gateway}"),
+            authorization
+                .updateVerificationGateway(*verification_gateway)
+                .into_transaction_request(),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::AddAuthorizedAddress { address } => AdminCall::new(
+            format!("Authorize {address} on processor"),
+            processor
+                .addAuthorizedAddress(*address)
+                .into_transaction_request(),
+            OwnableContract::Processor,
+        ),
+        AdminCommand::RemoveAuthorizedAddress { address } => AdminCall::new(
+            format!("Revoke {address} on processor"),
+            processor
+                .removeAuthorizedAddress(*address)
+                .into_transaction_request(),
+            OwnableContract::Processor,
+        ),
+        AdminCommand::AuthorizeForward => AdminCall::new(
+            format!("Add the {FORWARD_LABEL} standard authorization"),
+            authorize_forward_tx(rp, contracts),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::AddZkRegistry { vk, executor } => {
+            let executor = executor.or(my_address).ok_or_else(|| {
+                Error::Config("--executor is required with --safe-batch".to_string())
+            })?;
+            AdminCall::new(
+                format!("Allow {executor} to execute ZK messages of registry {ZK_REGISTRY}"),
+                register_zk_registry_tx(rp, contracts, executor, *vk),
+                OwnableContract::Authorization,
+            )
+        }
+        AdminCommand::ApproveLibrary { account, library } => {
+            let contract = OwnableContract::from(*account);
+            AdminCall::new(
+                format!("Approve library {library} on {contract}"),
+                BaseAccount::new(contracts.address_of(contract), rp)
+                    .approveLibrary(*library)
+                    .into_transaction_request(),
+                contract,
+            )
+        }
+        AdminCommand::Pause | AdminCommand::Resume if args.safe_batch.is_none() => {
+            let pause = matches!(args.command, AdminCommand::Pause);
+            let eth_client = signer(eth_client)?;
+            let outcome = match set_processor_paused(eth_client, rp, contracts, pause).await? {
+                Some(tx_hash) if pause => AdminOutcome::new("Processor paused", Some(tx_hash)),
+                Some(tx_hash) => AdminOutcome::new("Processor resumed", Some(tx_hash)),
+                None if pause => AdminOutcome::new("Processor is already paused", None),
+                None => AdminOutcome::new("Processor is not paused", None),
+            };
+            return output.print(&outcome);
+        }
+        AdminCommand::Pause => AdminCall::new(
+            "Pause the processor",
+            set_processor_paused_tx(rp, contracts, true),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::Resume => AdminCall::new(
+            "Resume the processor",
+            set_processor_paused_tx(rp, contracts, false),
+            OwnableContract::Authorization,
+        ),
+        AdminCommand::TransferOwnership {
+            contract,
+            new_owner,
+            yes,
+        } => {
+            let contract = OwnableContract::from(*contract);
+            let address = contracts.address_of(contract);
+
+            if *new_owner == Address::ZERO {
+                return Err(Error::Config(
+                    "refusing to transfer ownership to the zero address".to_string(),
+                ));
+            }
+
+            // Show what is about to happen and make sure we are allowed to do it before
+            // asking for confirmation
+            let current_owner = contract.owner(rp, address).await?;
+            eprintln!("{contract} at {address}");
+            eprintln!("  current owner: {current_owner}");
+            eprintln!("  new owner:     {new_owner}");
+
+            if current_owner == *new_owner {
+                return output.print(&AdminOutcome::new(
+                    format!("{contract} is already owned by {new_owner}, nothing to do"),
+                    None,
+                ));
+            }
+            if current_owner != caller {
+                return Err(Error::Config(format!(
+                    "{caller} is not the owner of {contract}"
+                )));
+            }
+            if !yes && !confirm(*new_owner)? {
+                return Err(Error::Config("ownership transfer aborted".to_string()));
+            }
+
+            AdminCall::new(
+                format!("Transfer ownership of {contract} to {new_owner}"),
+                contract.transfer_ownership_tx(rp, address, *new_owner),
+                contract,
+            )
+        }
+    };
+
+    let owner = call
+        .contract
+        .owner(rp, contracts.address_of(call.contract))
+        .await?;
+    let is_admin = call.contract == OwnableContract::Authorization
+        && authorization.adminAddresses(caller).call().await?._0;
+    if owner != caller && !is_admin {
+        log::warn!(
+            "{caller} is neither the owner of {} ({owner}) nor an Authorization admin, the call will likely revert",
+            call.contract
+        );
+    }
+
+    // Add the call to the Safe batch, or send it and check it went through
+    if let (Some(path), Some(safe)) = (&args.safe_batch, args.safe) {
+        let chain_id = rp.get_chain_id().await?;
+        let mut batch = SafeBatch::load_or_new(path, chain_id, safe)?;
+        batch.push(&call.step, &call.tx)?;
+        batch.save(path)?;
+        log::info!(
+            "Added to {}, import it in the Safe Transaction Builder",
+            path.display()
+        );
+
+        return output.print(&batch);
+    }
+
+    let receipt = signer(eth_client)?
+        .sign_and_send(call.tx)
+        .await
+        .map_err(Error::signing)
+        .step(call.step.clone())?;
+    let tx_hash = Some(receipt.transaction_hash);
+
+    let outcome = match args.command {
+        AdminCommand::UpdateProcessor { .. } => {
+            let current = authorization.processor().call().await?._0;
+            AdminOutcome::new(
+                format!("Authorization processor updated to: {current}"),
+                tx_hash,
+            )
+        }
+        AdminCommand::UpdateVerificationGateway { .. } => {
+            let current = authorization.verificationGateway().call().await?._0;
+            AdminOutcome::new(
+                format!("Authorization verification gateway updated to: {current}"),
+                tx_hash,
+            )
+        }
+        AdminCommand::TransferOwnership { new_owner, .. } => {
+            let address = contracts.address_of(call.contract);
+            let owner = call.contract.owner(rp, address).await?;
+            if owner != new_owner {
+                return Err(Error::UnexpectedState(format!(
+                    "{} is still owned by {owner}",
+                    call.contract
+                )));
+            }
+            AdminOutcome::new(
+                format!("{} ownership transferred to: {owner}", call.contract),
+                tx_hash,
+            )
+        }
+        _ => AdminOutcome::new(format!("Done: {}", call.step), tx_hash),
+    };
+
+    output.print(&outcome)
+}
+
+fn signer(eth_client: Option<&EthereumClient>) -> Result<&EthereumClient> {
+    eth_client.ok_or_else(|| Error::Config("sending the call needs a signer".to_string()))
+}
+
+/// Asks the operator to type the new owner again so a typo can't hand the contract away.
+///
+/// This prompt is the only safeguard: nothing on chain lets the transfer be accepted or
+/// cancelled once sent.
+fn confirm(new_owner: Address) -> Result<bool> {
+    eprint!("Ownership transfers are irreversible. Re-enter the new owner address to confirm: ");
+    io::stderr()
+        .flush()
+        .map_err(|e| Error::Config(e.to_string()))?;
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| Error::Config(format!("failed to read confirmation: {e}")))?;
+
+    Ok(line
+        .trim()
+        .parse::<Address>()
+        .is_ok_and(|typed| typed == new_owner))
+}
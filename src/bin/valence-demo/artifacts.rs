@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use simple_program_demo::artifacts::{
+    self, verify_deployed_code, ArtifactManifest, CompatibilityReport, ARTIFACTS_DIR,
+};
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::manifest::DeploymentManifest;
+use simple_program_demo::signer::read_provider;
+
+use crate::{Output, Verification};
+
+/// Repository the vendored artifacts are built from.
+const VALENCE_REPOSITORY: &str = "https://github.com/timewave-computer/valence-protocol";
+
+#[derive(Subcommand)]
+pub enum ArtifactsCommand {
+    /// Check the vendored artifacts against the hashes pinned in artifacts.json
+    Check,
+    /// Check that the deployed runtime code matches the vendored artifacts
+    VerifyDeployed,
+    /// Report the ABI changes of the artifacts in another directory, e.g. a newer Valence release
+    Compare {
+        /// Directory laid out like src/contracts (`<name>.sol/<name>.json`)
+        dir: PathBuf,
+    },
+    /// Replace the vendored artifacts with the ones in another directory and pin them
+    Update {
+        /// Directory laid out like src/contracts (`<name>.sol/<name>.json`)
+        dir: PathBuf,
+        /// Release tag or commit of valence-protocol the new artifacts were built from
+        #[arg(long)]
+        release: String,
+        /// Update even if the ABI changes break the bindings
+        #[arg(long)]
+        force: bool,
+    },
+    /// Record the hashes of the vendored artifacts in artifacts.json
+    Pin {
+        /// Release tag or commit of valence-protocol the artifacts were built from
+        #[arg(long)]
+        release: String,
+    },
+}
+
+pub async fn run(
+    command: ArtifactsCommand,
+    rpc_url: &str,
+    manifest_path: &Path,
+    output: Output,
+) -> Result<()> {
+    let dir = Path::new(ARTIFACTS_DIR);
+
+    match command {
+        ArtifactsCommand::Check => {
+            let manifest = ArtifactManifest::load(dir)?;
+            if manifest.release.is_none() {
+                log::warn!(
+                    "artifacts.json doesn't record the {VALENCE_REPOSITORY} release, \
+                     pin it with `artifacts pin --release <tag or commit>`"
+                );
+            }
+            let checks = manifest.check(dir)?;
+            output.verification(Verification::new(
+                checks,
+                "Artifacts match artifacts.json",
+                "Artifacts differ from artifacts.json",
+            ))
+        }
+        ArtifactsCommand::VerifyDeployed => {
+            let rp = read_provider(rpc_url)?;
+            let manifest = DeploymentManifest::load_or_discover(manifest_path, &rp)
+                .await
+                .step("loading deployment manifest")?;
+
+            let checks = verify_deployed_code(&rp, &manifest, dir).await?;
+            output.verification(Verification::new(
+                checks,
+                "Deployed code matches the artifacts",
+                "Deployed code differs from the artifacts",
+            ))
+        }
+        ArtifactsCommand::Compare { dir: new_dir } => {
+            output.print(&CompatibilityReport::compare(dir, &new_dir)?)
+        }
+        ArtifactsCommand::Update {
+            dir: new_dir,
+            release,
+            force,
+        } => {
+            let report = CompatibilityReport::compare(dir, &new_dir)?;
+            output.print(&report)?;
+
+            if !report.missing.is_empty() {
+                return Err(Error::Config(format!(
+                    "{} lacks {}",
+                    new_dir.display(),
+                    report.missing.join(", ")
+                )));
+            }
+            if !report.is_compatible() && !force {
+                return Err(Error::Config(
+                    "the new artifacts break the bindings, update the code and rerun with --force"
+                        .to_string(),
+                ));
+            }
+
+            let manifest =
+                artifacts::update(dir, &new_dir, VALENCE_REPOSITORY.to_string(), release)?;
+            log::info!(
+                "Pinned {} artifacts in {}",
+                manifest.artifacts.len(),
+                dir.display()
+            );
+            Ok(())
+        }
+        ArtifactsCommand::Pin { release } => {
+            let manifest = ArtifactManifest::pin(dir, VALENCE_REPOSITORY.to_string(), release)?;
+            manifest.save(dir)?;
+            log::info!(
+                "Pinned {} artifacts in {}",
+                manifest.artifacts.len(),
+                dir.display()
+            );
+            Ok(())
+        }
+    }
+}
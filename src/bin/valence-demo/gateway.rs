@@ -0,0 +1,170 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::{Address, B256};
+use clap::{ArgGroup, Subcommand};
+use serde::Serialize;
+use simple_program_demo::coprocessor::Coprocessor;
+use simple_program_demo::deploy::{artifact_bytecode, VerifierSource};
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::gas::GasReport;
+use simple_program_demo::gateway::{self, GatewayChange, GatewayState};
+use simple_program_demo::manifest::DeploymentManifest;
+use simple_program_demo::types::sol_types::SP1VerificationGateway;
+use valence_domain_clients::{
+    clients::ethereum::EthereumClient, evm::request_provider_client::RequestProviderClient,
+};
+
+use crate::Output;
+
+#[derive(Subcommand)]
+pub enum GatewayCommand {
+    /// Show the implementation, SP1 verifier, domain VK and owner of the verification gateway
+    Status,
+    /// Deploy a new implementation and upgrade the verification gateway proxy to it
+    Upgrade {
+        /// Foundry or Hardhat artifact of the new implementation,
+        /// defaults to the vendored SP1VerificationGateway
+        #[arg(long)]
+        artifact: Option<PathBuf>,
+    },
+    /// Set the domain VK to the one the coprocessor currently proves against
+    RotateDomainVk {
+        /// Domain VK to set instead of asking the coprocessor
+        #[arg(long)]
+        domain_vk: Option<B256>,
+    },
+    /// Point the verification gateway to another SP1 verifier
+    #[command(group(
+        ArgGroup::new("verifier")
+            .required(true)
+            .args(["sp1_verifier", "mock_verifier", "verifier_artifact"])
+    ))]
+    UpdateVerifier {
+        /// SP1 verifier already deployed on the chain
+        #[arg(long)]
+        sp1_verifier: Option<Address>,
+        /// Deploy a mock SP1 verifier accepting any proof, refused on non local chains
+        #[arg(long)]
+        mock_verifier: bool,
+        /// Deploy the SP1 verifier from a Foundry or Hardhat artifact
+        #[arg(long)]
+        verifier_artifact: Option<PathBuf>,
+    },
+}
+
+/// A gateway update and the gas of the transactions it took.
+#[derive(Serialize)]
+struct GatewayUpdate<V> {
+    #[serde(flatten)]
+    change: GatewayChange<V>,
+    gas: GasReport,
+}
+
+impl<V: fmt::Display> fmt::Display for GatewayUpdate<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.change)?;
+        if !self.gas.steps.is_empty() {
+            writeln!(f)?;
+            writeln!(f)?;
+            write!(f, "{}", self.gas)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a gateway command and records the new gateway settings in the manifest at `manifest_path`.
+pub async fn run(
+    command: GatewayCommand,
+    eth_client: &EthereumClient,
+    coprocessor: &Coprocessor,
+    mut manifest: DeploymentManifest,
+    manifest_path: &Path,
+    output: Output,
+) -> Result<()> {
+    let rp = eth_client
+        .get_request_provider()
+        .await
+        .map_err(Error::rpc)
+        .step("connecting to RPC")?;
+    let address = manifest.contracts.verification_gateway;
+    let mut gas = GasReport::default();
+
+    match command {
+        GatewayCommand::Status => {
+            let state = GatewayState::load(&rp, address).await?;
+            if state.implementation != manifest.verification_gateway_implementation {
+                log::warn!(
+                    "The manifest records implementation {}, the proxy delegates to {}",
+                    manifest.verification_gateway_implementation,
+                    state.implementation
+                );
+            }
+            output.print(&state)
+        }
+        GatewayCommand::Upgrade { artifact } => {
+            let init_code = match &artifact {
+                Some(path) => artifact_bytecode(path)?,
+                None => SP1VerificationGateway::BYTECODE.clone(),
+            };
+
+            let change = gateway::upgrade(eth_client, &rp, address, init_code, &mut gas).await?;
+            manifest.verification_gateway_implementation = change.after;
+            save(&manifest, manifest_path)?;
+
+            output.print(&GatewayUpdate { change, gas })
+        }
+        GatewayCommand::RotateDomainVk { domain_vk } => {
+            let (domain_vk, coprocessor_url) = match domain_vk {
+                Some(domain_vk) => (domain_vk, None),
+                None => {
+                    let health = coprocessor.health_check().await?;
+                    (health.domain_vk, Some(health.url))
+                }
+            };
+
+            let change =
+                gateway::rotate_domain_vk(eth_client, &rp, address, domain_vk, &mut gas).await?;
+            if change.tx_hash.is_some() || manifest.domain_vk != Some(change.after) {
+                manifest.domain_vk = Some(change.after);
+                if coprocessor_url.is_some() {
+                    manifest.coprocessor_url = coprocessor_url;
+                }
+                save(&manifest, manifest_path)?;
+            }
+
+            output.print(&GatewayUpdate { change, gas })
+        }
+        GatewayCommand::UpdateVerifier {
+            sp1_verifier,
+            mock_verifier,
+            verifier_artifact,
+        } => {
+            let source = match (sp1_verifier, verifier_artifact) {
+                (Some(address), _) => VerifierSource::Existing(address),
+                (None, Some(path)) => VerifierSource::Artifact(path),
+                (None, None) if mock_verifier => VerifierSource::Mock,
+                (None, None) => unreachable!("clap requires a verifier"),
+            };
+
+            let change =
+                gateway::update_verifier(eth_client, &rp, address, &source, &mut gas).await?;
+            if change.tx_hash.is_some() {
+                manifest.sp1_verifier = change.after;
+                manifest.sp1_verifier_origin = Some(source.origin());
+                save(&manifest, manifest_path)?;
+            }
+
+            output.print(&GatewayUpdate { change, gas })
+        }
+    }
+}
+
+fn save(manifest: &DeploymentManifest, path: &Path) -> Result<()> {
+    manifest.save(path)?;
+    log::info!("Manifest updated in {}", path.display());
+
+    Ok(())
+}
@@ -0,0 +1,730 @@
+mod admin;
+mod artifacts;
+mod gateway;
+
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use alloy::{
+    primitives::{Address, TxHash, B256},
+    rpc::types::TransactionReceipt,
+};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use simple_program_demo::coprocessor::{
+    Coprocessor, CoprocessorConfig, ProofTiming, DEFAULT_COPROCESSOR_URL,
+};
+use simple_program_demo::create2::{Create2Config, Create2Plan, CREATE2_FACTORY};
+use simple_program_demo::deploy::{
+    deploy_program, verify_deployment, Check, DeployConfig, Deployment, VerifierSource,
+    DEFAULT_MAX_FORWARD_AMOUNT,
+};
+use simple_program_demo::error::{Context, Error, Result};
+use simple_program_demo::gas::{GasComparison, GasReport, GasStep};
+use simple_program_demo::gateway::GatewayChange;
+use simple_program_demo::logging::{self, LogFormat};
+use simple_program_demo::manifest::{DeploymentManifest, MANIFEST_HELP};
+use simple_program_demo::plan::DeployPlan;
+use simple_program_demo::program::{Balances, ValenceProgram};
+use simple_program_demo::signer::{self, ReadProvider, SignerSource};
+use simple_program_demo::status::ProgramStatus;
+use simple_program_demo::vk::{self, VkStatus};
+use simple_program_demo::{FORWARDER, SP1_VERIFIER};
+use tracing::{info_span, Instrument};
+use valence_domain_clients::clients::ethereum::EthereumClient;
+
+use crate::{admin::AdminArgs, artifacts::ArtifactsCommand, gateway::GatewayCommand};
+
+#[derive(Parser)]
+#[command(
+    name = "valence-demo",
+    about = "Deploy, activate and inspect the Valence forwarding demo program"
+)]
+struct Cli {
+    /// Ethereum JSON-RPC endpoint
+    #[arg(long, global = true, default_value = "http://127.0.0.1:8545")]
+    rpc_url: String,
+
+    #[arg(long, global = true, default_value = "deployment.json", help = MANIFEST_HELP)]
+    manifest: PathBuf,
+
+    /// Coprocessor endpoint
+    #[arg(
+        long,
+        global = true,
+        env = "VALENCE_DEMO_COPROCESSOR_URL",
+        default_value = DEFAULT_COPROCESSOR_URL
+    )]
+    coprocessor_url: String,
+
+    /// Id of the coprocessor app, overrides the one in the manifest
+    #[arg(long, global = true, env = "VALENCE_DEMO_APP_ID")]
+    app_id: Option<String>,
+
+    /// Timeout in seconds of the coprocessor VK queries
+    #[arg(long, global = true, default_value_t = 30)]
+    coprocessor_timeout: u64,
+
+    /// Timeout in seconds of a coprocessor proof request
+    #[arg(long, global = true, default_value_t = 600)]
+    prove_timeout: u64,
+
+    /// The coprocessor is the local mock, which serves its VKs as bytes32
+    #[arg(long, global = true, env = "VALENCE_DEMO_MOCK_COPROCESSOR")]
+    mock_coprocessor: bool,
+
+    /// Encrypted JSON keystore of the signer
+    #[arg(long, global = true, conflicts_with_all = ["key", "mnemonic"])]
+    keystore: Option<PathBuf>,
+
+    /// Password of the keystore, asked for on the terminal when not set
+    #[arg(
+        long,
+        global = true,
+        env = "VALENCE_DEMO_KEYSTORE_PASSWORD",
+        hide_env_values = true
+    )]
+    keystore_password: Option<String>,
+
+    /// Mnemonic of the signer
+    #[arg(
+        long,
+        global = true,
+        env = "VALENCE_DEMO_MNEMONIC",
+        hide_env_values = true,
+        conflicts_with = "key"
+    )]
+    mnemonic: Option<String>,
+
+    /// Index of the account derived from the mnemonic
+    #[arg(long, global = true, default_value_t = 0)]
+    mnemonic_index: u32,
+
+    /// Hex encoded private key of the signer.
+    /// Without any signer option, the first anvil account is used on local chains
+    #[arg(long, global = true, env = "VALENCE_DEMO_KEY", hide_env_values = true)]
+    key: Option<String>,
+
+    /// Print results as JSON on stdout, progress is still logged to stderr
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Format of the logs on stderr: text, or json for one object per line
+    #[arg(long, global = true, env = "VALENCE_DEMO_LOG_FORMAT", default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Deploy and wire every contract of the program and write the manifest
+    Deploy(DeployArgs),
+    /// Print the addresses the contracts deployed through the CREATE2 factory get with the
+    /// given salt. The processor and the verification gateway proxy are deployed from the
+    /// deployer nonce and are not included
+    PredictAddresses {
+        #[arg(long)]
+        salt: B256,
+        #[arg(long, default_value_t = CREATE2_FACTORY)]
+        create2_factory: Address,
+    },
+    /// Forward tokens from the send account to the deposit account
+    #[command(group(ArgGroup::new("mode").required(true).args(["zk", "standard"])))]
+    Activate {
+        /// Authorize the forward with a coprocessor ZK proof
+        #[arg(long)]
+        zk: bool,
+        /// Authorize the forward with a standard on-chain authorization
+        #[arg(long)]
+        standard: bool,
+        /// Also forward through the other path and compare the gas of
+        /// `executeZKMessage` and `sendProcessorMessage`. Moves the tokens twice
+        #[arg(long)]
+        compare_gas: bool,
+        /// Record how long the coprocessor took to prove in this file, read by `program-metrics`
+        #[arg(long, env = "VALENCE_DEMO_PROOF_TIMING")]
+        proof_timing: Option<PathBuf>,
+    },
+    /// Summarize the live program: forwarder, balances, processor, authorizations,
+    /// owners and the last callbacks
+    Status {
+        /// Number of most recent callbacks to show
+        #[arg(long, default_value_t = 5)]
+        callbacks: usize,
+    },
+    /// Show the DEMO balances of the send and deposit accounts
+    Balances,
+    /// Check that the deployed contracts are wired to each other as expected
+    Verify,
+    /// Compare the domain and program VKs of the coprocessor with the ones on-chain
+    VkStatus {
+        /// Update the on-chain VKs that drifted from the coprocessor ones
+        #[arg(long)]
+        reconcile: bool,
+        /// Reconcile without asking for confirmation
+        #[arg(long, requires = "reconcile")]
+        yes: bool,
+    },
+    /// Administrative actions on the deployed program
+    Admin(AdminArgs),
+    /// Upgrade the verification gateway, rotate its domain VK or swap its SP1 verifier
+    #[command(subcommand)]
+    Gateway(GatewayCommand),
+    /// Track the vendored contract artifacts and check deployed code against them
+    #[command(subcommand)]
+    Artifacts(ArtifactsCommand),
+}
+
+#[derive(Args)]
+struct DeployArgs {
+    /// SP1 verifier the verification gateway checks proofs with
+    #[arg(long, default_value = SP1_VERIFIER)]
+    sp1_verifier: Address,
+    /// Deploy a mock SP1 verifier accepting any proof, for the mock coprocessor.
+    /// Refused on non local chains
+    #[arg(long, conflicts_with_all = ["sp1_verifier", "verifier_artifact"])]
+    mock_verifier: bool,
+    /// Deploy the SP1 verifier from a Foundry or Hardhat artifact (e.g. SP1VerifierGroth16.json),
+    /// for chains without the canonical verifier
+    #[arg(long, conflicts_with = "sp1_verifier")]
+    verifier_artifact: Option<PathBuf>,
+    /// Deploy through the CREATE2 factory with this salt, see `predict-addresses`
+    #[arg(long)]
+    salt: Option<B256>,
+    /// CREATE2 factory used with --salt
+    #[arg(long, default_value_t = CREATE2_FACTORY, requires = "salt")]
+    create2_factory: Address,
+    /// Write the unsigned transactions of the deployment to this file instead of sending them
+    #[arg(long)]
+    plan: Option<PathBuf>,
+    /// Deployer the plan is built for, defaults to the address of the signer
+    #[arg(long, requires = "plan")]
+    from: Option<Address>,
+    /// Hand every contract to this owner, e.g. a multisig, once the program is wired.
+    /// The deployer keeps ownership by default
+    #[arg(long)]
+    owner: Option<Address>,
+    /// Hand the Forwarder to the processor instead of the owner
+    #[arg(long)]
+    processor_owns_forwarder: bool,
+}
+
+impl DeployArgs {
+    /// Deployment parameters of the command.
+    fn config(&self, domain_vk: B256) -> DeployConfig {
+        let verifier = match &self.verifier_artifact {
+            Some(path) => VerifierSource::Artifact(path.clone()),
+            None if self.mock_verifier => VerifierSource::Mock,
+            None => VerifierSource::Existing(self.sp1_verifier),
+        };
+
+        let mut config = DeployConfig::new(verifier, domain_vk);
+        config.create2 = self.salt.map(|salt| Create2Config {
+            factory: self.create2_factory,
+            salt,
+        });
+        config.owner = self.owner;
+        config.processor_owns_forwarder = self.processor_owns_forwarder;
+
+        config
+    }
+}
+
+impl Cli {
+    fn signer_source(&self) -> SignerSource {
+        if let Some(path) = &self.keystore {
+            SignerSource::Keystore {
+                path: path.clone(),
+                password: self.keystore_password.clone(),
+            }
+        } else if let Some(key) = &self.key {
+            SignerSource::PrivateKey(key.clone())
+        } else if let Some(phrase) = &self.mnemonic {
+            SignerSource::Mnemonic {
+                phrase: phrase.clone(),
+                index: self.mnemonic_index,
+            }
+        } else {
+            SignerSource::Mnemonic {
+                phrase: signer::ANVIL_MNEMONIC.to_string(),
+                index: self.mnemonic_index,
+            }
+        }
+    }
+}
+
+/// How command results are written to stdout.
+#[derive(Clone, Copy)]
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn print<T: Serialize + fmt::Display>(&self, value: &T) -> Result<()> {
+        if self.json {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|e| Error::Config(format!("failed to serialize output: {e}")))?;
+            println!("{json}");
+        } else {
+            println!("{value}");
+        }
+
+        Ok(())
+    }
+
+    /// Prints the checks and fails if any of them did.
+    pub fn verification(&self, verification: Verification) -> Result<()> {
+        self.print(&verification)?;
+
+        if !verification.ok {
+            return Err(Error::UnexpectedState(verification.failure.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct DeployOutput {
+    manifest: DeploymentManifest,
+    gas: GasReport,
+}
+
+impl fmt::Display for DeployOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.manifest)?;
+        writeln!(f)?;
+        write!(f, "{}", self.gas)
+    }
+}
+
+#[derive(Serialize)]
+struct Activation {
+    mode: &'static str,
+    tx_hash: TxHash,
+    block_number: Option<u64>,
+    balances: Balances,
+    /// Id of the span the proof request was logged under, for ZK forwards. Generated locally,
+    /// the coprocessor doesn't know it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof_span_id: Option<String>,
+    /// Seconds the coprocessor took to generate the proof, for ZK forwards.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof_seconds: Option<f64>,
+    gas: GasReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_comparison: Option<GasComparison>,
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Forward ({}) executed in transaction {}",
+            self.mode, self.tx_hash
+        )?;
+        writeln!(f, "{}", self.balances)?;
+        if let (Some(span_id), Some(seconds)) = (&self.proof_span_id, self.proof_seconds) {
+            writeln!(f, "Proof generated in {seconds:.1}s (proof span {span_id})")?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.gas)?;
+        if let Some(comparison) = &self.gas_comparison {
+            writeln!(f)?;
+            writeln!(f)?;
+            write!(f, "{comparison}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers the ZK registry and forwards with a coprocessor proof, returning the
+/// `executeZKMessage` receipt and how long the proof took.
+#[tracing::instrument(name = "activation", skip_all, fields(mode = "zk"))]
+async fn activate_zk(
+    program: &ValenceProgram,
+    coprocessor: &Coprocessor,
+    manifest: &DeploymentManifest,
+    gas: &mut GasReport,
+) -> Result<(TransactionReceipt, ProofTiming)> {
+    coprocessor.health_check().await?;
+
+    let app_id = coprocessor.app_id(manifest.coprocessor_app_id.as_deref());
+    log::info!("Using coprocessor app {app_id}");
+    let program_vk = coprocessor.program_vk(app_id).await?;
+    gas.track(
+        "registering ZK registry",
+        program.register_zk_registry(program_vk),
+    )
+    .await?;
+    log::info!("ZK authorization created");
+
+    let (proof, timing) = coprocessor.prove(app_id, &json!({})).await?;
+    let receipt = gas
+        .track("executing ZK message", program.forward_via_zk(proof))
+        .instrument(info_span!(
+            "zk_message",
+            proof_span_id = %timing.span_id
+        ))
+        .await?;
+    log::info!(
+        "Proof {} executed in transaction {}",
+        timing.span_id,
+        receipt.transaction_hash
+    );
+
+    Ok((receipt, timing))
+}
+
+/// Adds the forward authorization and forwards through it, returning the
+/// `sendProcessorMessage` receipt.
+#[tracing::instrument(name = "activation", skip_all, fields(mode = "standard"))]
+async fn activate_standard(
+    program: &ValenceProgram,
+    gas: &mut GasReport,
+) -> Result<TransactionReceipt> {
+    gas.track("adding forward authorization", program.authorize_forward())
+        .await?;
+
+    gas.track(
+        "sending forward message",
+        program.forward_via_standard_auth(),
+    )
+    .await
+}
+
+#[derive(Serialize)]
+struct VkReconciliation {
+    changes: Vec<GatewayChange<B256>>,
+    status: VkStatus,
+    gas: GasReport,
+}
+
+impl fmt::Display for VkReconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        writeln!(f, "{}", self.status)?;
+        writeln!(f)?;
+        write!(f, "{}", self.gas)
+    }
+}
+
+/// Compares the coprocessor VKs with the chain and, with `reconcile`, updates the ones
+/// that drifted. Fails when they are out of sync and not reconciled. `eth_client` is only
+/// needed to reconcile.
+async fn vk_status(
+    rp: &ReadProvider,
+    eth_client: Option<&EthereumClient>,
+    coprocessor: &Coprocessor,
+    mut manifest: DeploymentManifest,
+    manifest_path: &Path,
+    reconcile: bool,
+    yes: bool,
+    output: Output,
+) -> Result<()> {
+    let app_id = coprocessor
+        .app_id(manifest.coprocessor_app_id.as_deref())
+        .to_string();
+    log::info!(
+        "Comparing VKs of coprocessor {} app {app_id}",
+        coprocessor.config().url
+    );
+
+    let status = VkStatus::load(rp, coprocessor, &app_id, &manifest).await?;
+    if status.in_sync() {
+        return output.print(&status);
+    }
+    if !reconcile {
+        output.print(&status)?;
+        return Err(Error::UnexpectedState(
+            "the on-chain VKs differ from the coprocessor ones, executeZKMessage will revert. Run vk-status --reconcile to update them".to_string(),
+        ));
+    }
+
+    eprintln!("{status}");
+    if !yes && !ask("Update the drifted VKs on-chain?")? {
+        return Err(Error::Config("reconciliation aborted".to_string()));
+    }
+
+    let eth_client = eth_client
+        .ok_or_else(|| Error::Config("reconciling the VKs needs a signer".to_string()))?;
+    let mut gas = GasReport::default();
+    let changes = vk::reconcile(eth_client, rp, &manifest.contracts, &status, &mut gas).await?;
+
+    if !status.domain_vk.in_sync() {
+        manifest.domain_vk = Some(status.domain_vk.coprocessor);
+        manifest.coprocessor_url = Some(coprocessor.config().url.clone());
+        manifest.save(manifest_path)?;
+        log::info!("Manifest updated in {}", manifest_path.display());
+    }
+
+    let status = VkStatus::compare(
+        rp,
+        &manifest.contracts,
+        status.domain_vk.coprocessor,
+        status.program_vk.coprocessor,
+    )
+    .await?;
+    output.print(&VkReconciliation {
+        changes,
+        status,
+        gas,
+    })
+}
+
+/// Asks a yes/no question on the terminal, anything but "y" or "yes" is a no.
+fn ask(question: &str) -> Result<bool> {
+    eprint!("{question} [y/N] ");
+    io::stderr()
+        .flush()
+        .map_err(|e| Error::Config(e.to_string()))?;
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| Error::Config(format!("failed to read answer: {e}")))?;
+
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[derive(Serialize)]
+pub struct Verification {
+    ok: bool,
+    checks: Vec<Check>,
+    #[serde(skip)]
+    success: &'static str,
+    #[serde(skip)]
+    failure: &'static str,
+}
+
+impl Verification {
+    pub fn new(checks: Vec<Check>, success: &'static str, failure: &'static str) -> Self {
+        Self {
+            ok: checks.iter().all(|check| check.ok),
+            checks,
+            success,
+            failure,
+        }
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{check}")?;
+        }
+        write!(f, "{}", if self.ok { self.success } else { self.failure })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    logging::init(cli.log_format)?;
+    let output = Output { json: cli.json };
+
+    // Artifact commands work without a signer
+    if let Command::Artifacts(command) = cli.command {
+        return artifacts::run(command, &cli.rpc_url, &cli.manifest, output).await;
+    }
+
+    let coprocessor = Coprocessor::new(CoprocessorConfig {
+        url: cli.coprocessor_url.clone(),
+        app_id: cli.app_id.clone(),
+        timeout: Duration::from_secs(cli.coprocessor_timeout),
+        prove_timeout: Duration::from_secs(cli.prove_timeout),
+        mock: cli.mock_coprocessor,
+    });
+
+    // Planning signs nothing, so it works for any deployer and on any chain
+    if let Command::Deploy(
+        args @ DeployArgs {
+            plan: Some(path), ..
+        },
+    ) = &cli.command
+    {
+        let health = coprocessor.health_check().await?;
+        let config = args.config(health.domain_vk);
+        let from = match args.from {
+            Some(from) => from,
+            None => cli.signer_source().signer()?.address(),
+        };
+
+        let rp = signer::read_provider(&cli.rpc_url)?;
+        let plan = DeployPlan::new(&rp, from, &config).await?;
+        plan.save(path)?;
+        log::info!("Unsigned transactions written to {}", path.display());
+
+        return output.print(&plan);
+    }
+
+    if let Command::Deploy(args) = &cli.command {
+        let eth_client = signer::eth_client(&cli.rpc_url, &cli.signer_source()).await?;
+        // We need to get the domain vk of the coprocessor to initialize the verification gateway
+        let health = coprocessor.health_check().await?;
+        let config = args.config(health.domain_vk);
+
+        let Deployment { mut manifest, gas } = deploy_program(&eth_client, &config).await?;
+        manifest.coprocessor_url = Some(health.url);
+        manifest.coprocessor_app_id = cli.app_id.clone();
+        manifest.save(&cli.manifest)?;
+        log::info!("Manifest written to {}", cli.manifest.display());
+
+        if manifest.contracts.forwarder != FORWARDER {
+            log::warn!(
+                "The circuit forwards through {FORWARDER}, set FORWARDER_LIBRARY_CONTRACT to {} before using activate --zk",
+                manifest.contracts.forwarder
+            );
+        }
+
+        return output.print(&DeployOutput { manifest, gas });
+    }
+
+    if let Command::PredictAddresses {
+        salt,
+        create2_factory,
+    } = &cli.command
+    {
+        let plan = Create2Plan::new(
+            Create2Config {
+                factory: *create2_factory,
+                salt: *salt,
+            },
+            cli.signer_source().signer()?.address(),
+            DEFAULT_MAX_FORWARD_AMOUNT,
+        );
+        return output.print(&plan);
+    }
+
+    // Only the commands sending transactions load the signer, the others read the chain
+    let rp = signer::read_provider(&cli.rpc_url)?;
+    let rpc_url = cli.rpc_url.clone();
+    let signer_source = cli.signer_source();
+    let eth_client = || signer::eth_client(&rpc_url, &signer_source);
+    let manifest = DeploymentManifest::load_or_discover(&cli.manifest, &rp)
+        .await
+        .step("loading deployment manifest")?;
+
+    match cli.command {
+        Command::Deploy(_) | Command::PredictAddresses { .. } => {
+            unreachable!("handled above")
+        }
+        Command::Activate {
+            zk,
+            compare_gas,
+            proof_timing,
+            ..
+        } => {
+            let program = ValenceProgram::from_manifest(eth_client().await?, &manifest);
+            let mut gas = GasReport::default();
+            let mut timing = None;
+
+            let receipt = if zk {
+                let (receipt, proof) =
+                    activate_zk(&program, &coprocessor, &manifest, &mut gas).await?;
+                timing = Some(proof);
+                receipt
+            } else {
+                activate_standard(&program, &mut gas).await?
+            };
+
+            let gas_comparison = if compare_gas {
+                log::info!("Forwarding again through the other path to compare gas");
+                let (zk_receipt, standard_receipt) = if zk {
+                    let other = activate_standard(&program, &mut gas).await?;
+                    (receipt.clone(), other)
+                } else {
+                    let (other, proof) =
+                        activate_zk(&program, &coprocessor, &manifest, &mut gas).await?;
+                    timing = Some(proof);
+                    (other, receipt.clone())
+                };
+
+                Some(GasComparison {
+                    zk: GasStep::from_receipt("executeZKMessage", &zk_receipt),
+                    standard: GasStep::from_receipt("sendProcessorMessage", &standard_receipt),
+                })
+            } else {
+                None
+            };
+
+            if let (Some(path), Some(timing)) = (&proof_timing, &timing) {
+                timing.save(path)?;
+                log::info!("Proof timing recorded in {}", path.display());
+            }
+
+            output.print(&Activation {
+                mode: if zk { "zk" } else { "standard" },
+                tx_hash: receipt.transaction_hash,
+                block_number: receipt.block_number,
+                balances: program.balances().await.step("querying balances")?,
+                proof_seconds: timing.as_ref().map(|timing| timing.seconds),
+                proof_span_id: timing.map(|timing| timing.span_id),
+                gas,
+                gas_comparison,
+            })
+        }
+        Command::Status { callbacks } => output.print(
+            &ProgramStatus::load(&rp, manifest, callbacks)
+                .await
+                .step("reading program status")?,
+        ),
+        Command::Balances => output.print(
+            &Balances::load(&rp, &manifest.contracts, manifest.token)
+                .await
+                .step("querying balances")?,
+        ),
+        Command::Verify => output.verification(Verification::new(
+            verify_deployment(&rp, &manifest).await?,
+            "Deployment verified",
+            "Deployment has wiring errors",
+        )),
+        Command::VkStatus { reconcile, yes } => {
+            let eth_client = if reconcile {
+                Some(eth_client().await?)
+            } else {
+                None
+            };
+            vk_status(
+                &rp,
+                eth_client.as_ref(),
+                &coprocessor,
+                manifest,
+                &cli.manifest,
+                reconcile,
+                yes,
+                output,
+            )
+            .await
+        }
+        Command::Admin(args) => {
+            let eth_client = if args.needs_signer() {
+                Some(eth_client().await?)
+            } else {
+                None
+            };
+            admin::run(args, &rp, eth_client.as_ref(), &manifest, output).await
+        }
+        Command::Gateway(command) => {
+            gateway::run(
+                command,
+                &eth_client().await?,
+                &coprocessor,
+                manifest,
+                &cli.manifest,
+                output,
+            )
+            .await
+        }
+        Command::Artifacts(_) => unreachable!("handled above"),
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    fmt, fs,
+    future::Future,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    hex::FromHex,
+    primitives::{Bytes, FixedBytes},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sp1_sdk::{HashableKey, SP1VerifyingKey};
+use tracing::{info, info_span, Instrument};
+use valence_domain_clients::{
+    clients::coprocessor::CoprocessorClient, coprocessor::base_client::CoprocessorBaseClient,
+};
+
+use crate::{
+    error::{Context, Error, Result},
+    program::ZkProof,
+    COPROCESSOR_APP_ID,
+};
+
+/// Public coprocessor service, also used by the `cargo-valence` commands in the README.
+pub const DEFAULT_COPROCESSOR_URL: &str = "http://prover.timewave.computer:37281";
+
+/// Where and how to reach the coprocessor for a run.
+#[derive(Debug, Clone)]
+pub struct CoprocessorConfig {
+    pub url: String,
+    /// Id of the deployed controller, overrides the one recorded in the manifest.
+    pub app_id: Option<String>,
+    /// Timeout of the VK queries.
+    pub timeout: Duration,
+    /// Timeout of a proof request, which waits for the proof to be generated.
+    pub prove_timeout: Duration,
+    /// The coprocessor is the local mock, which serves its VKs already as `bytes32`.
+    pub mock: bool,
+}
+
+impl Default for CoprocessorConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_COPROCESSOR_URL.to_string(),
+            app_id: None,
+            timeout: Duration::from_secs(30),
+            prove_timeout: Duration::from_secs(600),
+            mock: false,
+        }
+    }
+}
+
+/// Coprocessor reported by [`Coprocessor::health_check`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CoprocessorHealth {
+    pub url: String,
+    pub domain_vk: FixedBytes<32>,
+}
+
+impl fmt::Display for CoprocessorHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "coprocessor {} (domain VK {})", self.url, self.domain_vk)
+    }
+}
+
+/// How long the coprocessor took to generate a proof, kept on disk by `activate --zk`
+/// for the metrics exporter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofTiming {
+    /// Id of the span the request was logged under, see [`Coprocessor::prove`].
+    #[serde(alias = "request_id")]
+    pub span_id: String,
+    pub app_id: String,
+    pub seconds: f64,
+    /// Unix timestamp of when the proof was received.
+    pub finished_at: u64,
+}
+
+impl ProofTiming {
+    /// Reads the timing at `path`, `None` if no proof has been recorded there yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!(
+                "failed to read proof timing {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| Error::Config(format!("invalid proof timing {}: {e}", path.display())))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("failed to serialize proof timing: {e}")))?;
+
+        fs::write(path, contents).map_err(|e| {
+            Error::Config(format!(
+                "failed to write proof timing {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+/// Coprocessor client bound to a [`CoprocessorConfig`].
+pub struct Coprocessor {
+    client: CoprocessorClient,
+    config: CoprocessorConfig,
+}
+
+impl Coprocessor {
+    pub fn new(config: CoprocessorConfig) -> Self {
+        Self {
+            client: CoprocessorClient::new(config.url.clone()),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &CoprocessorConfig {
+        &self.config
+    }
+
+    /// The configured app id, falling back to `default` (usually the one in the manifest)
+    /// and then to [`COPROCESSOR_APP_ID`].
+    pub fn app_id<'a>(&'a self, default: Option<&'a str>) -> &'a str {
+        self.config
+            .app_id
+            .as_deref()
+            .or(default)
+            .unwrap_or(COPROCESSOR_APP_ID)
+    }
+
+    /// Makes sure the coprocessor answers and reports which domain VK it proves against.
+    pub async fn health_check(&self) -> Result<CoprocessorHealth> {
+        let domain_vk = self
+            .domain_vk()
+            .await
+            .step(format!("checking coprocessor {}", self.config.url))?;
+
+        let health = CoprocessorHealth {
+            url: self.config.url.clone(),
+            domain_vk,
+        };
+        info!("Using {health}");
+
+        Ok(health)
+    }
+
+    /// Domain verification key of the coprocessor, used to initialize the verification gateway.
+    pub async fn domain_vk(&self) -> Result<FixedBytes<32>> {
+        let vk = with_timeout(self.config.timeout, self.client.get_domain_vk())
+            .await
+            .step("fetching coprocessor domain VK")?;
+
+        self.decode_vk(&vk).step("decoding coprocessor domain VK")
+    }
+
+    /// Verification key of the deployed circuit, registered on the Authorization contract.
+    pub async fn program_vk(&self, app_id: &str) -> Result<FixedBytes<32>> {
+        let vk = with_timeout(self.config.timeout, self.client.get_vk(app_id))
+            .await
+            .step("fetching program VK")?;
+
+        self.decode_vk(&vk).step("decoding program VK")
+    }
+
+    /// Decodes a VK served by the coprocessor, see [`vk_bytes32`]. Only the mock coprocessor
+    /// serves raw `bytes32` keys, a real one serving 32 bytes is an error.
+    fn decode_vk(&self, vk: &[u8]) -> Result<FixedBytes<32>> {
+        if self.config.mock && vk.len() == 32 {
+            return Ok(FixedBytes::<32>::from_slice(vk));
+        }
+
+        vk_bytes32(vk)
+    }
+
+    /// Requests a proof of the circuit for the given controller arguments, along with how
+    /// long the coprocessor took to generate it.
+    ///
+    /// The coprocessor API takes no request id, so an id is generated locally and set on the
+    /// `coprocessor_request` span, for our logs to tie the proof to the transaction that
+    /// carries it. The coprocessor never sees it, it can't be looked up in the service logs.
+    pub async fn prove(&self, app_id: &str, args: &Value) -> Result<(ZkProof, ProofTiming)> {
+        let span_id = span_id();
+        let span = info_span!("coprocessor_request", span_id, app_id);
+        let started = Instant::now();
+        let zkp = with_timeout(self.config.prove_timeout, self.client.prove(app_id, args))
+            .instrument(span.clone())
+            .await
+            .step(format!("requesting proof {span_id}"))?;
+        let timing = ProofTiming {
+            span_id,
+            app_id: app_id.to_string(),
+            seconds: started.elapsed().as_secs_f64(),
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        };
+        span.in_scope(|| info!("Proof generated in {:.1}s", timing.seconds));
+
+        // extract the program and domain parameters by decoding the zkp
+        let (proof_program, inputs_program) = zkp
+            .program
+            .decode()
+            .map_err(Error::decode)
+            .step("decoding program proof")?;
+        let (proof_domain, inputs_domain) = zkp
+            .domain
+            .decode()
+            .map_err(Error::decode)
+            .step("decoding domain proof")?;
+
+        let proof = ZkProof {
+            program_inputs: Bytes::from(inputs_program),
+            program_proof: Bytes::from(proof_program),
+            domain_inputs: Bytes::from(inputs_domain),
+            domain_proof: Bytes::from(proof_domain),
+        };
+
+        Ok((proof, timing))
+    }
+}
+
+/// Unique enough local id for a proof request: the time it was sent, the process and a counter.
+fn span_id() -> String {
+    static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    format!(
+        "{millis:x}-{:x}-{}",
+        std::process::id(),
+        REQUESTS.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+async fn with_timeout<T, E>(
+    timeout: Duration,
+    request: impl Future<Output = std::result::Result<T, E>>,
+) -> Result<T>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    tokio::time::timeout(timeout, request)
+        .await
+        .map_err(|_| Error::coprocessor(format!("no response after {}s", timeout.as_secs())))?
+        .map_err(Error::coprocessor)
+}
+
+/// Converts a bincode encoded `SP1VerifyingKey` into the `bytes32` form stored on-chain.
+pub fn vk_bytes32(vk: &[u8]) -> Result<FixedBytes<32>> {
+    let sp1_vk: SP1VerifyingKey = bincode::deserialize(vk).map_err(Error::decode)?;
+
+    FixedBytes::<32>::from_hex(sp1_vk.bytes32()).map_err(Error::decode)
+}
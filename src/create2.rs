@@ -0,0 +1,301 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{address, Address, Bytes, FixedBytes, Keccak256, B256, U256},
+    providers::Provider,
+    sol_types::{SolConstructor, SolValue},
+    transports::Transport,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    admin::ProgramContracts,
+    deploy::{forwarder_config, DeployConfig},
+    error::{Context, Error, Result},
+    plan::{gateway_initialize_data, PlanBuilder, PlannedProgram},
+    types::sol_types::{
+        processor_contract::LiteProcessor, Authorization, BaseAccount, ERC1967Proxy, Forwarder,
+        MockERC20, SP1VerificationGateway,
+    },
+};
+
+/// Deterministic deployment proxy, deployed on most chains and preinstalled in anvil.
+/// It deploys the init code following the 32 bytes salt of its calldata.
+pub const CREATE2_FACTORY: Address = address!("0x4e59b44847b379578588920cA78FbF26c0B4956C");
+
+/// Deploys the program contracts at addresses derived from a salt instead of the deployer nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Create2Config {
+    pub factory: Address,
+    pub salt: B256,
+}
+
+impl Create2Config {
+    pub fn new(salt: B256) -> Self {
+        Self {
+            factory: CREATE2_FACTORY,
+            salt,
+        }
+    }
+
+    /// Salt of a single contract, so that one salt gives every contract its own address.
+    pub fn salt_for(&self, label: &str) -> B256 {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.salt);
+        hasher.update(label);
+        hasher.finalize()
+    }
+
+    pub fn address_of(&self, label: &str, init_code: &[u8]) -> Address {
+        self.factory
+            .create2_from_code(self.salt_for(label), init_code)
+    }
+}
+
+fn init_code(bytecode: &Bytes, constructor_args: Vec<u8>) -> Bytes {
+    [&bytecode[..], constructor_args.as_slice()].concat().into()
+}
+
+/// Init code and address of every contract deployed through the factory.
+///
+/// The processor and the verification gateway proxy take their owner from the sender, which
+/// would be the factory, so both are deployed from the deployer nonce instead, the proxy
+/// initialized in its constructor. Their addresses aren't known when the factory contracts
+/// are created: the Authorization contract and the Forwarder get the owner as a placeholder,
+/// their constructors reject the zero address, and are pointed to the real contracts once
+/// they exist.
+#[derive(Debug, Clone, Serialize)]
+pub struct Create2Plan {
+    #[serde(flatten)]
+    pub config: Create2Config,
+    pub contracts: Vec<PlannedContract>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedContract {
+    pub label: &'static str,
+    pub address: Address,
+    #[serde(skip)]
+    pub init_code: Bytes,
+}
+
+impl Create2Plan {
+    /// Computes the addresses the program gets when deployed by `owner`, with a forwarder
+    /// moving at most `max_forward_amount` per execution.
+    pub fn new(create2: Create2Config, owner: Address, max_forward_amount: U256) -> Self {
+        let mut plan = Self {
+            config: create2,
+            contracts: Vec::new(),
+        };
+
+        let account = init_code(
+            &BaseAccount::BYTECODE,
+            BaseAccount::constructorCall {
+                _owner: owner,
+                _libraries: vec![],
+            }
+            .abi_encode(),
+        );
+        let send_account = plan.push("send-account", account.clone());
+        let deposit_account = plan.push("deposit-account", account);
+
+        let token = plan.push(
+            "token",
+            init_code(
+                &MockERC20::BYTECODE,
+                MockERC20::constructorCall {
+                    name: "Demo Token".to_string(),
+                    symbol: "DEMO".to_string(),
+                    decimalsValue: 18,
+                }
+                .abi_encode(),
+            ),
+        );
+
+        plan.push(
+            "verification-gateway-implementation",
+            SP1VerificationGateway::BYTECODE.clone(),
+        );
+        plan.push(
+            "authorization",
+            init_code(
+                &Authorization::BYTECODE,
+                Authorization::constructorCall {
+                    _owner: owner,
+                    _processor: owner,
+                    _verificationGateway: owner,
+                    _storeCallbacks: true,
+                }
+                .abi_encode(),
+            ),
+        );
+
+        let forwarder_config =
+            forwarder_config(send_account, deposit_account, token, max_forward_amount);
+        plan.push(
+            "forwarder",
+            init_code(
+                &Forwarder::BYTECODE,
+                Forwarder::constructorCall {
+                    _owner: owner,
+                    _processor: owner,
+                    _config: forwarder_config.abi_encode().into(),
+                }
+                .abi_encode(),
+            ),
+        );
+
+        plan
+    }
+
+    fn push(&mut self, label: &'static str, init_code: Bytes) -> Address {
+        let address = self.config.address_of(label, &init_code);
+        self.contracts.push(PlannedContract {
+            label,
+            address,
+            init_code,
+        });
+
+        address
+    }
+
+    pub fn address(&self, label: &str) -> Result<Address> {
+        self.contracts
+            .iter()
+            .find(|contract| contract.label == label)
+            .map(|contract| contract.address)
+            .ok_or_else(|| Error::Config(format!("{label} is not part of the CREATE2 plan")))
+    }
+}
+
+impl fmt::Display for Create2Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "CREATE2 factory {}, salt {}",
+            self.config.factory, self.config.salt
+        )?;
+        for (i, contract) in self.contracts.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:<36} {}", contract.label, contract.address)?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the factory calls of the [`Create2Plan`] of `config` and the wiring of the program
+/// to `builder`, after checking the factory exists and none of the addresses is used.
+pub(crate) async fn plan_program<T, P>(
+    rp: &P,
+    builder: &mut PlanBuilder,
+    config: &DeployConfig,
+    create2: Create2Config,
+    sp1_verifier: Address,
+) -> Result<PlannedProgram>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let factory_code = rp
+        .get_code_at(create2.factory)
+        .await
+        .step("querying CREATE2 factory code")?;
+    if factory_code.is_empty() {
+        return Err(Error::Config(format!(
+            "no CREATE2 factory at {}",
+            create2.factory
+        )));
+    }
+
+    let plan = Create2Plan::new(create2, builder.from, config.max_forward_amount);
+    for contract in &plan.contracts {
+        let code = rp
+            .get_code_at(contract.address)
+            .await
+            .step(format!("querying code at {}", contract.address))?;
+        if !code.is_empty() {
+            return Err(Error::Config(format!(
+                "{} is already deployed at {} with salt {}, use another salt",
+                contract.label, contract.address, create2.salt
+            )));
+        }
+        builder.create2(&create2, contract);
+    }
+
+    let send_account = plan.address("send-account")?;
+    let deposit_account = plan.address("deposit-account")?;
+    let token = plan.address("token")?;
+    let verification_gateway_implementation =
+        plan.address("verification-gateway-implementation")?;
+    let authorization = plan.address("authorization")?;
+    let forwarder = plan.address("forwarder")?;
+
+    let processor = builder.create(
+        "deploying processor",
+        LiteProcessor::deploy_builder(
+            rp,
+            FixedBytes::<32>::default(),
+            Address::ZERO,
+            0,
+            vec![authorization],
+        )
+        .into_transaction_request(),
+    );
+    let verification_gateway = builder.create(
+        "deploying verification gateway proxy",
+        ERC1967Proxy::deploy_builder(
+            rp,
+            verification_gateway_implementation,
+            gateway_initialize_data(sp1_verifier, config.domain_vk),
+        )
+        .into_transaction_request(),
+    );
+
+    builder.call(
+        "minting DEMO to send account",
+        MockERC20::new(token, rp)
+            .mint(send_account, config.mint_amount)
+            .into_transaction_request(),
+    );
+    builder.call(
+        "approving forwarder library on send account",
+        BaseAccount::new(send_account, rp)
+            .approveLibrary(forwarder)
+            .into_transaction_request(),
+    );
+
+    let authorization_contract = Authorization::new(authorization, rp);
+    builder.call(
+        "pointing authorization to the processor",
+        authorization_contract
+            .updateProcessor(processor)
+            .into_transaction_request(),
+    );
+    builder.call(
+        "pointing authorization to the verification gateway",
+        authorization_contract
+            .updateVerificationGateway(verification_gateway)
+            .into_transaction_request(),
+    );
+    builder.call(
+        "pointing forwarder to the processor",
+        Forwarder::new(forwarder, rp)
+            .updateProcessor(processor)
+            .into_transaction_request(),
+    );
+
+    Ok(PlannedProgram {
+        contracts: ProgramContracts {
+            authorization,
+            processor,
+            forwarder,
+            verification_gateway,
+            send_account,
+            deposit_account,
+        },
+        verification_gateway_implementation,
+        token,
+    })
+}
@@ -0,0 +1,492 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{hex, Address, Bytes, FixedBytes, U256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    transports::Transport,
+};
+use log::info;
+use serde::Serialize;
+use serde_json::Value;
+use valence_domain_clients::{
+    clients::ethereum::EthereumClient,
+    evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
+};
+
+use crate::{
+    admin::{ControlReport, OwnableContract},
+    create2::Create2Config,
+    error::{Context, Error, Result},
+    gas::GasReport,
+    gateway,
+    manifest::{DeploymentManifest, VerifierOrigin},
+    plan::DeployPlan,
+    signer::is_local_dev_chain,
+    types::{
+        forwarder::{ForwarderConfig, ForwardingConfig, IntervalType},
+        sol_types::{
+            processor_contract::LiteProcessor, Authorization, BaseAccount, Forwarder,
+            SP1VerificationGateway,
+        },
+    },
+};
+
+/// Init code of a contract whose runtime code is a single `STOP`: every call to it succeeds,
+/// so as an SP1 verifier it accepts any proof. Only meant for local chains.
+pub const MOCK_SP1_VERIFIER_INIT_CODE: [u8; 13] = hex!("6001600c60003960016000f300");
+
+/// DEMO tokens minted to the send account by default.
+pub const DEFAULT_MINT_AMOUNT: U256 = U256::from_limbs([1000, 0, 0, 0]);
+
+/// Maximum amount the forwarder moves per execution by default.
+pub const DEFAULT_MAX_FORWARD_AMOUNT: U256 = U256::from_limbs([100, 0, 0, 0]);
+
+/// Where the SP1 verifier of the verification gateway comes from.
+#[derive(Debug, Clone)]
+pub enum VerifierSource {
+    /// Verifier already deployed on the chain, like the canonical [`crate::SP1_VERIFIER`] on mainnet.
+    Existing(Address),
+    /// Deploys [`MOCK_SP1_VERIFIER_INIT_CODE`], which lets the mock coprocessor proofs through.
+    Mock,
+    /// Deploys the Foundry or Hardhat artifact of a verifier without constructor arguments,
+    /// such as `SP1VerifierGroth16` or `SP1VerifierPlonk` from sp1-contracts.
+    Artifact(PathBuf),
+}
+
+impl VerifierSource {
+    pub fn origin(&self) -> VerifierOrigin {
+        match self {
+            VerifierSource::Existing(_) => VerifierOrigin::Existing,
+            VerifierSource::Mock => VerifierOrigin::Mock,
+            VerifierSource::Artifact(path) => VerifierOrigin::Artifact(path.clone()),
+        }
+    }
+}
+
+/// Reads the creation bytecode of a compiled contract artifact.
+pub fn artifact_bytecode(path: &Path) -> Result<Bytes> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("failed to read artifact {}: {e}", path.display())))?;
+    let artifact: Value = serde_json::from_str(&contents)
+        .map_err(|e| Error::Config(format!("invalid artifact {}: {e}", path.display())))?;
+
+    // Foundry nests the bytecode in an object, Hardhat stores it directly
+    let bytecode = artifact["bytecode"]["object"]
+        .as_str()
+        .or_else(|| artifact["bytecode"].as_str())
+        .ok_or_else(|| Error::Config(format!("no bytecode in artifact {}", path.display())))?;
+
+    bytecode.parse().map_err(|e| {
+        Error::Config(format!(
+            "invalid bytecode in artifact {}: {e}",
+            path.display()
+        ))
+    })
+}
+
+/// SP1 verifier of a [`VerifierSource`], once checked against the chain.
+pub(crate) enum ResolvedVerifier {
+    /// Existing contract.
+    Existing(Address),
+    /// Init code of the verifier to deploy.
+    Deploy(Bytes),
+}
+
+/// Makes sure an existing verifier is a contract, and refuses the mock verifier unless the
+/// node is a local development chain.
+pub(crate) async fn resolve_verifier<T, P>(
+    rp: &P,
+    source: &VerifierSource,
+) -> Result<ResolvedVerifier>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    match source {
+        VerifierSource::Existing(address) => {
+            let code = rp
+                .get_code_at(*address)
+                .await
+                .step("querying SP1 verifier code")?;
+            if code.is_empty() {
+                return Err(Error::Config(format!(
+                    "no contract at SP1 verifier {address}, deploy one with --mock-verifier or --verifier-artifact"
+                )));
+            }
+
+            Ok(ResolvedVerifier::Existing(*address))
+        }
+        VerifierSource::Mock => {
+            if !is_local_dev_chain(rp).await? {
+                return Err(Error::Config(
+                    "refusing to deploy the mock SP1 verifier, which accepts any proof, on a \
+                     non local chain, use --sp1-verifier or --verifier-artifact"
+                        .to_string(),
+                ));
+            }
+
+            Ok(ResolvedVerifier::Deploy(Bytes::from_static(
+                &MOCK_SP1_VERIFIER_INIT_CODE,
+            )))
+        }
+        VerifierSource::Artifact(path) => Ok(ResolvedVerifier::Deploy(artifact_bytecode(path)?)),
+    }
+}
+
+/// Deploys the SP1 verifier described by `source`, or makes sure the existing one is a contract.
+///
+/// The mock verifier is refused unless the node is a local development chain.
+pub async fn deploy_verifier(
+    eth_client: &EthereumClient,
+    source: &VerifierSource,
+    gas: &mut GasReport,
+) -> Result<Address> {
+    let rp = eth_client
+        .get_request_provider()
+        .await
+        .map_err(Error::rpc)
+        .step("connecting to RPC")?;
+    let init_code = match resolve_verifier(&rp, source).await? {
+        ResolvedVerifier::Existing(address) => return Ok(address),
+        ResolvedVerifier::Deploy(init_code) => init_code,
+    };
+
+    let tx = TransactionRequest::default().with_deploy_code(init_code);
+    let verifier = gas.deploy(eth_client, tx, "deploying SP1 verifier").await?;
+    info!("SP1 verifier ({}) deployed at: {verifier}", source.origin());
+
+    Ok(verifier)
+}
+
+/// Parameters of a program deployment.
+#[derive(Debug, Clone)]
+pub struct DeployConfig {
+    /// SP1 verifier the verification gateway checks proofs with.
+    pub verifier: VerifierSource,
+    /// Domain verification key of the coprocessor, as returned by `SP1VerifyingKey::bytes32`.
+    pub domain_vk: FixedBytes<32>,
+    /// DEMO tokens minted to the send account.
+    pub mint_amount: U256,
+    /// Maximum amount the forwarder moves per execution.
+    pub max_forward_amount: U256,
+    /// Deploy through a CREATE2 factory instead of from the deployer nonce.
+    pub create2: Option<Create2Config>,
+    /// Owner every contract is handed to once the program is wired, the deployer keeps
+    /// ownership when unset.
+    pub owner: Option<Address>,
+    /// Hand the Forwarder to the processor instead of `owner`.
+    pub processor_owns_forwarder: bool,
+}
+
+impl DeployConfig {
+    pub fn new(verifier: VerifierSource, domain_vk: FixedBytes<32>) -> Self {
+        Self {
+            verifier,
+            domain_vk,
+            mint_amount: DEFAULT_MINT_AMOUNT,
+            max_forward_amount: DEFAULT_MAX_FORWARD_AMOUNT,
+            create2: None,
+            owner: None,
+            processor_owns_forwarder: false,
+        }
+    }
+}
+
+/// Configuration of the forwarder moving at most `max_amount` of `token` per execution.
+pub(crate) fn forwarder_config(
+    send_account: Address,
+    deposit_account: Address,
+    token: Address,
+    max_amount: U256,
+) -> ForwarderConfig {
+    ForwarderConfig {
+        inputAccount: send_account,
+        outputAccount: deposit_account,
+        forwardingConfigs: vec![ForwardingConfig {
+            tokenAddress: token,
+            maxAmount: max_amount,
+        }],
+        intervalType: IntervalType::BLOCKS,
+        minInterval: 1,
+    }
+}
+
+/// Result of [`deploy_program`]: the deployed addresses and the gas of every transaction.
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub manifest: DeploymentManifest,
+    pub gas: GasReport,
+}
+
+/// Deploys and wires every contract of the program with `eth_client` as the initial owner.
+///
+/// Sends the transactions of the [`DeployPlan`] of the deployer, so a reviewed plan is
+/// exactly what gets deployed, through the CREATE2 factory as well when `config.create2`
+/// is set.
+#[tracing::instrument(name = "deploy", skip_all, fields(deployer = %eth_client.signer().address()))]
+pub async fn deploy_program(
+    eth_client: &EthereumClient,
+    config: &DeployConfig,
+) -> Result<Deployment> {
+    let rp = eth_client
+        .get_request_provider()
+        .await
+        .map_err(Error::rpc)
+        .step("connecting to RPC")?;
+    let plan = DeployPlan::build(&rp, eth_client.signer().address(), config).await?;
+
+    let mut gas = GasReport::default();
+    for planned in plan.transactions {
+        let receipt = gas
+            .send(eth_client, planned.transaction, &planned.step)
+            .await?;
+        if let Some(expected) = planned.contract_address {
+            // CREATE2 deployments are calls to the factory, only the code tells they happened
+            let created = receipt.contract_address.unwrap_or(expected);
+            let code = rp
+                .get_code_at(expected)
+                .await
+                .step(format!("querying code at {expected}"))?;
+            if created != expected || code.is_empty() {
+                return Err(Error::UnexpectedState(format!(
+                    "{} didn't create the planned {expected}, \
+                     did the deployer send another transaction?",
+                    planned.step
+                )));
+            }
+        }
+    }
+
+    let manifest = plan.predicted;
+    info!(
+        "Program deployed, Authorization at: {}",
+        manifest.contracts.authorization
+    );
+    // The ownership transfers are part of the plan, this only checks they took effect
+    hand_over(eth_client, &rp, &manifest, &mut gas).await?;
+
+    Ok(Deployment { manifest, gas })
+}
+
+/// Owner every contract of `manifest` has once [`deploy_program`] is done.
+pub fn expected_owners(
+    manifest: &DeploymentManifest,
+    deployer: Address,
+) -> Vec<(OwnableContract, Address)> {
+    let contracts = &manifest.contracts;
+    let owner = manifest.owner.unwrap_or(deployer);
+
+    OwnableContract::ALL
+        .into_iter()
+        .map(|contract| {
+            let expected = match contract {
+                OwnableContract::Forwarder if manifest.processor_owns_forwarder => {
+                    contracts.processor
+                }
+                _ => owner,
+            };
+            (contract, expected)
+        })
+        .collect()
+}
+
+/// Step name of the ownership transfer of `contract`.
+pub(crate) fn transfer_step(contract: OwnableContract) -> String {
+    format!(
+        "transferring {} ownership",
+        contract.to_string().to_lowercase()
+    )
+}
+
+/// Transfers every contract still owned by the deployer to its final owner, see
+/// [`expected_owners`], then checks the deployer holds no privileged role anymore.
+pub(crate) async fn hand_over<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    manifest: &DeploymentManifest,
+    gas: &mut GasReport,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let deployer = eth_client.signer().address();
+    let contracts = &manifest.contracts;
+
+    for (contract, new_owner) in expected_owners(manifest, deployer) {
+        let address = contracts.address_of(contract);
+        if new_owner == deployer || contract.owner(rp, address).await? != deployer {
+            continue;
+        }
+
+        let tx = contract.transfer_ownership_tx(rp, address, new_owner);
+        gas.send(eth_client, tx, &transfer_step(contract)).await?;
+        info!("{contract} ownership transferred to: {new_owner}");
+    }
+
+    let checks = verify_ownership(rp, manifest, deployer).await?;
+    if let Some(check) = checks.iter().find(|check| !check.ok) {
+        return Err(Error::UnexpectedState(format!(
+            "ownership was not handed over, {check}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks every contract is owned as [`expected_owners`] says and, when the program was
+/// handed to another owner, that `deployer` is left without any privileged role.
+pub async fn verify_ownership<T, P>(
+    rp: &P,
+    manifest: &DeploymentManifest,
+    deployer: Address,
+) -> Result<Vec<Check>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let from_block = manifest.first_block(rp).await?;
+    let report = ControlReport::load(rp, &manifest.contracts, from_block).await?;
+    let expected = expected_owners(manifest, deployer);
+
+    let mut checks = vec![];
+    for (contract, _, owner) in &report.owners {
+        if let Some((_, expected)) = expected.iter().find(|(c, _)| c == contract) {
+            checks.push(Check::expect(
+                format!("{contract} owner"),
+                *owner,
+                *expected,
+            ));
+        }
+    }
+
+    if manifest.owner.is_some_and(|owner| owner != deployer) {
+        checks.push(Check::expect(
+            format!("{deployer} is an Authorization admin"),
+            report.authorization_admins.contains(&deployer),
+            false,
+        ));
+        checks.push(Check::expect(
+            format!("{deployer} is authorized on the processor"),
+            report.processor_authorized_addresses.contains(&deployer),
+            false,
+        ));
+    }
+
+    Ok(checks)
+}
+
+/// Outcome of a single wiring check performed by [`verify_deployment`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    pub(crate) fn expect<V: PartialEq + fmt::Display>(
+        name: impl Into<String>,
+        actual: V,
+        expected: V,
+    ) -> Self {
+        let ok = actual == expected;
+        let detail = if ok {
+            actual.to_string()
+        } else {
+            format!("expected {expected}, found {actual}")
+        };
+
+        Self {
+            name: name.into(),
+            ok,
+            detail,
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.ok { "ok  " } else { "FAIL" };
+        write!(f, "[{mark}] {}: {}", self.name, self.detail)
+    }
+}
+
+/// Checks that the contracts in the manifest exist and are wired to each other
+/// the way [`deploy_program`] leaves them.
+pub async fn verify_deployment<T, P>(rp: &P, manifest: &DeploymentManifest) -> Result<Vec<Check>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let contracts = &manifest.contracts;
+    let authorization = Authorization::new(contracts.authorization, rp);
+    let processor = LiteProcessor::new(contracts.processor, rp);
+    let forwarder = Forwarder::new(contracts.forwarder, rp);
+    let send_account = BaseAccount::new(contracts.send_account, rp);
+    let verification_gateway = SP1VerificationGateway::new(contracts.verification_gateway, rp);
+
+    let forwarder_config = forwarder.config().call().await?;
+
+    Ok(vec![
+        Check::expect("chain id", rp.get_chain_id().await?, manifest.chain_id),
+        Check::expect(
+            "authorization processor",
+            authorization.processor().call().await?._0,
+            contracts.processor,
+        ),
+        Check::expect(
+            "authorization verification gateway",
+            authorization.verificationGateway().call().await?._0,
+            contracts.verification_gateway,
+        ),
+        Check::expect(
+            "authorization allowed on processor",
+            processor
+                .authorizedAddresses(contracts.authorization)
+                .call()
+                .await?
+                ._0,
+            true,
+        ),
+        Check::expect(
+            "forwarder processor",
+            forwarder.processor().call().await?._0,
+            contracts.processor,
+        ),
+        Check::expect(
+            "forwarder input account",
+            forwarder_config.inputAccount,
+            contracts.send_account,
+        ),
+        Check::expect(
+            "forwarder output account",
+            forwarder_config.outputAccount,
+            contracts.deposit_account,
+        ),
+        Check::expect(
+            "forwarder approved on send account",
+            send_account
+                .approvedLibraries(contracts.forwarder)
+                .call()
+                .await?
+                ._0,
+            true,
+        ),
+        Check::expect(
+            "verification gateway implementation",
+            gateway::implementation(rp, contracts.verification_gateway).await?,
+            manifest.verification_gateway_implementation,
+        ),
+        Check::expect(
+            "verification gateway verifier",
+            verification_gateway.verifier().call().await?._0,
+            manifest.sp1_verifier,
+        ),
+    ])
+}
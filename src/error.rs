@@ -0,0 +1,110 @@
+use alloy::{
+    primitives::{Address, TxHash},
+    rpc::types::TransactionReceipt,
+    transports::TransportError,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by the library and the demo binaries.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The node could not be reached or rejected the request.
+    #[error("RPC error: {0}")]
+    Rpc(#[source] BoxError),
+
+    /// A contract call reverted or its return data could not be read.
+    #[error("contract call failed: {0}")]
+    ContractCall(#[from] alloy::contract::Error),
+
+    /// The transaction could not be signed, broadcast or confirmed.
+    #[error("failed to sign and send transaction: {0}")]
+    Signing(#[source] BoxError),
+
+    /// The coprocessor returned an error or an unexpected response.
+    #[error("coprocessor error: {0}")]
+    Coprocessor(#[source] BoxError),
+
+    /// Data that should follow the contracts' ABI could not be decoded.
+    #[error("ABI decode error: {0}")]
+    AbiDecode(#[from] alloy::sol_types::Error),
+
+    /// A verifying key or proof returned by the coprocessor could not be decoded.
+    #[error("failed to decode coprocessor payload: {0}")]
+    Decode(#[source] BoxError),
+
+    /// The receipt of a mined transaction lacks a field we depend on.
+    #[error("receipt of transaction {tx_hash} has no {field}")]
+    MissingReceiptField {
+        tx_hash: TxHash,
+        field: &'static str,
+    },
+
+    /// An on-chain check after a state change did not hold.
+    #[error("unexpected on-chain state: {0}")]
+    UnexpectedState(String),
+
+    /// The local event index could not be read or written.
+    #[error("index database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// Invalid or missing configuration.
+    #[error("configuration error: {0}")]
+    Config(String),
+
+    /// Any of the above, annotated with the step that was being executed.
+    #[error("{step}: {source}")]
+    Step {
+        step: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    pub fn rpc(error: impl Into<BoxError>) -> Self {
+        Error::Rpc(error.into())
+    }
+
+    pub fn signing(error: impl Into<BoxError>) -> Self {
+        Error::Signing(error.into())
+    }
+
+    pub fn coprocessor(error: impl Into<BoxError>) -> Self {
+        Error::Coprocessor(error.into())
+    }
+
+    pub fn decode(error: impl Into<BoxError>) -> Self {
+        Error::Decode(error.into())
+    }
+}
+
+impl From<TransportError> for Error {
+    fn from(error: TransportError) -> Self {
+        Error::Rpc(error.into())
+    }
+}
+
+/// Attaches the step that failed to an error, e.g. which contract was being deployed.
+pub trait Context<T> {
+    fn step(self, step: impl Into<String>) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn step(self, step: impl Into<String>) -> Result<T> {
+        self.map_err(|error| Error::Step {
+            step: step.into(),
+            source: Box::new(error.into()),
+        })
+    }
+}
+
+/// Returns the address of the contract created by the transaction.
+pub fn deployed_address(receipt: &TransactionReceipt) -> Result<Address> {
+    receipt.contract_address.ok_or(Error::MissingReceiptField {
+        tx_hash: receipt.transaction_hash,
+        field: "contract address",
+    })
+}
@@ -0,0 +1,190 @@
+use std::{fmt, future::Future, time::Instant};
+
+use alloy::{
+    primitives::{
+        utils::{format_ether, ParseUnits, Unit},
+        Address, TxHash, U256,
+    },
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use serde::Serialize;
+use tracing::{field, info, info_span, Instrument};
+use valence_domain_clients::{clients::ethereum::EthereumClient, evm::base_client::EvmBaseClient};
+
+use crate::error::{deployed_address, Context, Error, Result};
+
+/// Gas paid by a single transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct GasStep {
+    pub step: String,
+    pub tx_hash: TxHash,
+    pub gas_used: u64,
+    /// Effective gas price in wei, base fee plus priority fee.
+    pub effective_gas_price: u128,
+    /// Cost in wei.
+    pub cost: U256,
+}
+
+impl GasStep {
+    pub fn from_receipt(step: impl Into<String>, receipt: &TransactionReceipt) -> Self {
+        Self {
+            step: step.into(),
+            tx_hash: receipt.transaction_hash,
+            gas_used: receipt.gas_used,
+            effective_gas_price: receipt.effective_gas_price,
+            cost: U256::from(receipt.gas_used) * U256::from(receipt.effective_gas_price),
+        }
+    }
+}
+
+/// Gas of every transaction sent for a deployment or an activation, in sending order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GasReport {
+    pub steps: Vec<GasStep>,
+    pub total_gas_used: u64,
+    /// Total cost in wei.
+    pub total_cost: U256,
+}
+
+impl GasReport {
+    pub fn record(&mut self, step: impl Into<String>, receipt: &TransactionReceipt) {
+        let step = GasStep::from_receipt(step, receipt);
+        self.total_gas_used += step.gas_used;
+        self.total_cost += step.cost;
+        self.steps.push(step);
+    }
+
+    /// Waits for the transaction of `step` and records its gas.
+    ///
+    /// The transaction runs in a `step` span, which gets the contract, transaction hash,
+    /// block, gas used and elapsed time once it is mined.
+    pub async fn track(
+        &mut self,
+        step: &str,
+        tx: impl Future<Output = Result<TransactionReceipt>>,
+    ) -> Result<TransactionReceipt> {
+        let span = info_span!(
+            "step",
+            step,
+            contract = field::Empty,
+            tx_hash = field::Empty,
+            block = field::Empty,
+            gas = field::Empty,
+            elapsed_ms = field::Empty,
+        );
+        let started = Instant::now();
+        let receipt = tx.instrument(span.clone()).await.step(step)?;
+
+        if let Some(contract) = receipt.contract_address.or(receipt.to) {
+            span.record("contract", field::display(contract));
+        }
+        span.record("tx_hash", field::display(receipt.transaction_hash));
+        if let Some(block) = receipt.block_number {
+            span.record("block", block);
+        }
+        span.record("gas", receipt.gas_used);
+        span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+        span.in_scope(|| info!("{step} mined"));
+
+        self.record(step, &receipt);
+
+        Ok(receipt)
+    }
+
+    /// Signs and sends `tx`, recording its gas under `step`.
+    pub async fn send(
+        &mut self,
+        eth_client: &EthereumClient,
+        tx: TransactionRequest,
+        step: &str,
+    ) -> Result<TransactionReceipt> {
+        self.track(step, async {
+            eth_client.sign_and_send(tx).await.map_err(Error::signing)
+        })
+        .await
+    }
+
+    /// Same as [`GasReport::send`] for a contract creation, returning the contract address.
+    pub async fn deploy(
+        &mut self,
+        eth_client: &EthereumClient,
+        tx: TransactionRequest,
+        step: &str,
+    ) -> Result<Address> {
+        let receipt = self.send(eth_client, tx, step).await?;
+        deployed_address(&receipt).step(step)
+    }
+
+    /// Appends the steps of another report, e.g. the activation after its setup.
+    pub fn extend(&mut self, other: GasReport) {
+        self.total_gas_used += other.total_gas_used;
+        self.total_cost += other.total_cost;
+        self.steps.extend(other.steps);
+    }
+}
+
+fn gwei(wei: u128) -> String {
+    ParseUnits::from(wei).format_units(Unit::GWEI)
+}
+
+impl fmt::Display for GasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<48} {:>10} {:>22} {:>22}",
+            "Step", "Gas used", "Gas price (gwei)", "Cost (ETH)"
+        )?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "{:<48} {:>10} {:>22} {:>22}",
+                step.step,
+                step.gas_used,
+                gwei(step.effective_gas_price),
+                format_ether(step.cost)
+            )?;
+        }
+        write!(
+            f,
+            "{:<48} {:>10} {:>22} {:>22}",
+            "Total",
+            self.total_gas_used,
+            "",
+            format_ether(self.total_cost)
+        )
+    }
+}
+
+/// Gas of the same forward executed through `executeZKMessage` and `sendProcessorMessage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GasComparison {
+    pub zk: GasStep,
+    pub standard: GasStep,
+}
+
+impl GasComparison {
+    /// Extra gas of the ZK path, negative if it is cheaper.
+    pub fn zk_overhead(&self) -> i128 {
+        i128::from(self.zk.gas_used) - i128::from(self.standard.gas_used)
+    }
+}
+
+impl fmt::Display for GasComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<34} {:>10} gas ({} ETH)",
+            "Forward via executeZKMessage:",
+            self.zk.gas_used,
+            format_ether(self.zk.cost)
+        )?;
+        writeln!(
+            f,
+            "{:<34} {:>10} gas ({} ETH)",
+            "Forward via sendProcessorMessage:",
+            self.standard.gas_used,
+            format_ether(self.standard.cost)
+        )?;
+        write!(f, "ZK overhead: {:+} gas", self.zk_overhead())
+    }
+}
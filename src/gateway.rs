@@ -0,0 +1,315 @@
+use std::fmt;
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{b256, Address, Bytes, TxHash, B256, U256},
+    providers::Provider,
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    transports::Transport,
+};
+use log::info;
+use serde::Serialize;
+use valence_domain_clients::{clients::ethereum::EthereumClient, evm::base_client::EvmBaseClient};
+
+use crate::{
+    deploy::{deploy_verifier, VerifierSource},
+    error::{Context, Error, Result},
+    gas::GasReport,
+    types::sol_types::SP1VerificationGateway,
+};
+
+/// ERC1967 storage slot holding the implementation behind a proxy,
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`.
+pub const ERC1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// Storage slot of the domain VK in `SP1VerificationGateway`, which has no getter for it.
+/// The verifier is in slot 0 and the program VKs mapping in slot 2, the `Ownable` and
+/// `Initializable` state lives in ERC-7201 namespaced slots. The artifact has no storage
+/// layout, the slot is checked against a deployed gateway by the integration tests.
+pub const DOMAIN_VK_SLOT: U256 = U256::from_limbs([1, 0, 0, 0]);
+
+/// Reads the implementation the verification gateway proxy currently delegates to.
+pub async fn implementation<T, P>(rp: &P, proxy: Address) -> Result<Address>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let word = rp
+        .get_storage_at(proxy, ERC1967_IMPLEMENTATION_SLOT.into())
+        .await?;
+
+    Ok(Address::from_word(word.into()))
+}
+
+/// Reads the domain VK the verification gateway checks domain proofs against.
+pub async fn domain_vk<T, P>(rp: &P, gateway: Address) -> Result<B256>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let word = rp.get_storage_at(gateway, DOMAIN_VK_SLOT).await?;
+
+    Ok(word.into())
+}
+
+/// On-chain configuration of the verification gateway proxy.
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayState {
+    pub address: Address,
+    pub implementation: Address,
+    pub verifier: Address,
+    pub domain_vk: B256,
+    pub owner: Address,
+}
+
+impl GatewayState {
+    pub async fn load<T, P>(rp: &P, gateway: Address) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let contract = SP1VerificationGateway::new(gateway, rp);
+
+        Ok(Self {
+            address: gateway,
+            implementation: implementation(rp, gateway).await?,
+            verifier: contract.verifier().call().await?._0,
+            domain_vk: domain_vk(rp, gateway).await?,
+            owner: contract.owner().call().await?._0,
+        })
+    }
+}
+
+impl fmt::Display for GatewayState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Verification Gateway:     {}", self.address)?;
+        writeln!(f, "Implementation:           {}", self.implementation)?;
+        writeln!(f, "SP1 verifier:             {}", self.verifier)?;
+        writeln!(f, "Domain VK:                {}", self.domain_vk)?;
+        write!(f, "Owner:                    {}", self.owner)
+    }
+}
+
+/// A gateway setting read before and after an update, `tx_hash` is `None` when it
+/// already had the requested value.
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayChange<V> {
+    pub setting: &'static str,
+    pub before: V,
+    pub after: V,
+    pub tx_hash: Option<TxHash>,
+}
+
+impl<V: fmt::Display> fmt::Display for GatewayChange<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tx_hash {
+            Some(tx_hash) => write!(
+                f,
+                "{} updated from {} to {} (transaction {tx_hash})",
+                self.setting, self.before, self.after
+            ),
+            None => write!(f, "{} is already {}", self.setting, self.after),
+        }
+    }
+}
+
+/// Fails early when `caller` can't make owner-only calls on the gateway, rather than
+/// after deploying a contract for nothing.
+async fn ensure_owner<T, P>(rp: &P, gateway: Address, caller: Address) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let owner = SP1VerificationGateway::new(gateway, rp)
+        .owner()
+        .call()
+        .await?
+        ._0;
+    if owner != caller {
+        return Err(Error::Config(format!(
+            "{caller} is not the owner of the verification gateway ({owner})"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Deploys `init_code` as the new gateway implementation and upgrades the proxy to it.
+///
+/// The implementation must be UUPS upgradeable, `upgradeToAndCall` reverts otherwise and
+/// the proxy keeps delegating to the current one.
+pub async fn upgrade<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    gateway: Address,
+    init_code: Bytes,
+    gas: &mut GasReport,
+) -> Result<GatewayChange<Address>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    ensure_owner(rp, gateway, eth_client.signer().address()).await?;
+    let before = implementation(rp, gateway).await?;
+
+    let tx = TransactionRequest::default().with_deploy_code(init_code);
+    let new_implementation = gas
+        .deploy(
+            eth_client,
+            tx,
+            "deploying verification gateway implementation",
+        )
+        .await?;
+    info!("Verification Gateway implementation deployed at: {new_implementation}");
+
+    let uuid = SP1VerificationGateway::new(new_implementation, rp)
+        .proxiableUUID()
+        .call()
+        .await
+        .step("querying proxiableUUID of the new implementation")?
+        ._0;
+    if uuid != ERC1967_IMPLEMENTATION_SLOT {
+        return Err(Error::Config(format!(
+            "{new_implementation} is not a UUPS implementation, proxiableUUID is {uuid}"
+        )));
+    }
+
+    let upgrade_tx = SP1VerificationGateway::new(gateway, rp)
+        .upgradeToAndCall(new_implementation, Bytes::new())
+        .into_transaction_request();
+    let receipt = gas
+        .send(
+            eth_client,
+            upgrade_tx,
+            "upgrading verification gateway proxy",
+        )
+        .await?;
+
+    let upgraded = receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.address() == gateway)
+        .filter_map(|log| log.log_decode::<SP1VerificationGateway::Upgraded>().ok())
+        .any(|log| log.inner.data.implementation == new_implementation);
+    if !upgraded {
+        return Err(Error::UnexpectedState(format!(
+            "transaction {} did not emit Upgraded({new_implementation})",
+            receipt.transaction_hash
+        )));
+    }
+
+    let after = implementation(rp, gateway).await?;
+    expect_updated("implementation", after, new_implementation)?;
+    info!("Verification Gateway upgraded from {before} to {after}");
+
+    Ok(change("implementation", before, after, &receipt))
+}
+
+/// Sets the domain VK of the gateway, e.g. to the new `get_domain_vk` of the coprocessor
+/// after it rotated its domain circuit. Nothing is sent when the gateway already has it.
+pub async fn rotate_domain_vk<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    gateway: Address,
+    new_domain_vk: B256,
+    gas: &mut GasReport,
+) -> Result<GatewayChange<B256>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let before = domain_vk(rp, gateway).await?;
+    if before == new_domain_vk {
+        return Ok(GatewayChange {
+            setting: "domain VK",
+            before,
+            after: before,
+            tx_hash: None,
+        });
+    }
+    ensure_owner(rp, gateway, eth_client.signer().address()).await?;
+
+    let tx = SP1VerificationGateway::new(gateway, rp)
+        .updateDomainVK(new_domain_vk)
+        .into_transaction_request();
+    let receipt = gas
+        .send(eth_client, tx, "updating verification gateway domain VK")
+        .await?;
+
+    let after = domain_vk(rp, gateway).await?;
+    expect_updated("domain VK", after, new_domain_vk)?;
+    info!("Verification Gateway domain VK rotated from {before} to {after}");
+
+    Ok(change("domain VK", before, after, &receipt))
+}
+
+/// Points the gateway to the SP1 verifier of `source`, deploying it if needed.
+pub async fn update_verifier<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    gateway: Address,
+    source: &VerifierSource,
+    gas: &mut GasReport,
+) -> Result<GatewayChange<Address>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let contract = SP1VerificationGateway::new(gateway, rp);
+    let before = contract.verifier().call().await?._0;
+    if let VerifierSource::Existing(address) = source {
+        if *address == before {
+            return Ok(GatewayChange {
+                setting: "SP1 verifier",
+                before,
+                after: before,
+                tx_hash: None,
+            });
+        }
+    }
+    ensure_owner(rp, gateway, eth_client.signer().address()).await?;
+
+    let new_verifier = deploy_verifier(eth_client, source, gas).await?;
+    let tx = contract
+        .updateVerifier(new_verifier)
+        .into_transaction_request();
+    let receipt = gas
+        .send(eth_client, tx, "updating verification gateway verifier")
+        .await?;
+
+    let after = contract.verifier().call().await?._0;
+    expect_updated("SP1 verifier", after, new_verifier)?;
+    info!("Verification Gateway verifier updated from {before} to {after}");
+
+    Ok(change("SP1 verifier", before, after, &receipt))
+}
+
+fn change<V>(
+    setting: &'static str,
+    before: V,
+    after: V,
+    receipt: &TransactionReceipt,
+) -> GatewayChange<V> {
+    GatewayChange {
+        setting,
+        before,
+        after,
+        tx_hash: Some(receipt.transaction_hash),
+    }
+}
+
+fn expect_updated<V: PartialEq + fmt::Display>(
+    setting: &str,
+    actual: V,
+    expected: V,
+) -> Result<()> {
+    if actual != expected {
+        return Err(Error::UnexpectedState(format!(
+            "verification gateway {setting} is {actual} after the update, expected {expected}"
+        )));
+    }
+
+    Ok(())
+}
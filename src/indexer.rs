@@ -0,0 +1,751 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use alloy::{
+    primitives::{Address, Bytes, TxHash, B256, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    transports::Transport,
+};
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    admin::ProgramContracts,
+    error::{Context, Error, Result},
+    types::sol_types::{processor_contract::LiteProcessor, Authorization, Forwarder, MockERC20},
+};
+
+/// Blocks requested per `eth_getLogs` call, within the limits of most public RPC providers.
+pub const DEFAULT_BATCH_SIZE: u64 = 2000;
+
+/// Times a batch is read before giving up when its blocks keep changing under it.
+const BATCH_ATTEMPTS: usize = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+-- Hash of every block holding an indexed event, and of the last indexed block,
+-- compared with the chain to detect reorgs
+CREATE TABLE IF NOT EXISTS blocks (
+    number INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    block_number INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_hash TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    contract TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+CREATE INDEX IF NOT EXISTS events_kind ON events (kind);
+CREATE INDEX IF NOT EXISTS events_tx_hash ON events (tx_hash);
+";
+
+/// Name of the `IProcessor.ExecutionResult` value reported in callbacks.
+pub fn execution_result_name(result: u8) -> &'static str {
+    match result {
+        0 => "success",
+        1 => "rejected",
+        2 => "partially-executed",
+        _ => "unknown",
+    }
+}
+
+/// Program event decoded from a log of the Authorization, processor, Forwarder or token
+/// contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ProgramEvent {
+    AuthorizationAdded {
+        label: String,
+    },
+    AuthorizationRemoved {
+        label: String,
+    },
+    CallbackReceived {
+        execution_id: u64,
+        execution_result: u8,
+        executed_count: u64,
+        data: Bytes,
+    },
+    CallbackSent {
+        execution_id: u64,
+        result: u8,
+        executed_count: U256,
+    },
+    ProcessorWasPaused,
+    ProcessorWasResumed,
+    /// Ownership change of the Authorization, processor or Forwarder contract.
+    OwnershipTransferred {
+        previous_owner: Address,
+        new_owner: Address,
+    },
+    /// Token transfer from or to one of the program accounts.
+    Transfer {
+        from: Address,
+        to: Address,
+        value: U256,
+    },
+}
+
+impl ProgramEvent {
+    /// Decodes the log if it is one of the indexed events of the program.
+    pub fn decode(log: &Log, contracts: &ProgramContracts, token: Address) -> Option<Self> {
+        let address = log.address();
+
+        let ownable = [
+            contracts.authorization,
+            contracts.processor,
+            contracts.forwarder,
+        ];
+        if ownable.contains(&address) {
+            if let Ok(event) = log.log_decode::<Forwarder::OwnershipTransferred>() {
+                let event = event.inner.data;
+                return Some(Self::OwnershipTransferred {
+                    previous_owner: event.previousOwner,
+                    new_owner: event.newOwner,
+                });
+            }
+        }
+
+        if address == contracts.authorization {
+            if let Ok(event) = log.log_decode::<Authorization::AuthorizationAdded>() {
+                let event = event.inner.data;
+                return Some(Self::AuthorizationAdded { label: event.label });
+            }
+            if let Ok(event) = log.log_decode::<Authorization::AuthorizationRemoved>() {
+                let event = event.inner.data;
+                return Some(Self::AuthorizationRemoved { label: event.label });
+            }
+            if let Ok(event) = log.log_decode::<Authorization::CallbackReceived>() {
+                let event = event.inner.data;
+                return Some(Self::CallbackReceived {
+                    execution_id: event.executionId,
+                    execution_result: u8::from(event.executionResult),
+                    executed_count: event.executedCount,
+                    data: event.data,
+                });
+            }
+        } else if address == contracts.processor {
+            if let Ok(event) = log.log_decode::<LiteProcessor::CallbackSent>() {
+                let event = event.inner.data;
+                return Some(Self::CallbackSent {
+                    execution_id: event.executionId,
+                    result: u8::from(event.result),
+                    executed_count: event.executedCount,
+                });
+            }
+            if log
+                .log_decode::<LiteProcessor::ProcessorWasPaused>()
+                .is_ok()
+            {
+                return Some(Self::ProcessorWasPaused);
+            }
+            if log
+                .log_decode::<LiteProcessor::ProcessorWasResumed>()
+                .is_ok()
+            {
+                return Some(Self::ProcessorWasResumed);
+            }
+        } else if address == token {
+            if let Ok(event) = log.log_decode::<MockERC20::Transfer>() {
+                let event = event.inner.data;
+                let accounts = [contracts.send_account, contracts.deposit_account];
+                if accounts.contains(&event.from) || accounts.contains(&event.to) {
+                    return Some(Self::Transfer {
+                        from: event.from,
+                        to: event.to,
+                        value: event.value,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProgramEvent::AuthorizationAdded { .. } => "authorization-added",
+            ProgramEvent::AuthorizationRemoved { .. } => "authorization-removed",
+            ProgramEvent::CallbackReceived { .. } => "callback-received",
+            ProgramEvent::CallbackSent { .. } => "callback-sent",
+            ProgramEvent::ProcessorWasPaused => "processor-was-paused",
+            ProgramEvent::ProcessorWasResumed => "processor-was-resumed",
+            ProgramEvent::OwnershipTransferred { .. } => "ownership-transferred",
+            ProgramEvent::Transfer { .. } => "transfer",
+        }
+    }
+}
+
+/// Event stored in the index, with where it was emitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedEvent {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub block_hash: B256,
+    pub tx_hash: TxHash,
+    pub contract: Address,
+    #[serde(flatten)]
+    pub event: ProgramEvent,
+}
+
+impl fmt::Display for IndexedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10} {:>5}  {:<24} {}",
+            self.block_number,
+            self.log_index,
+            self.event.kind(),
+            self.tx_hash
+        )
+    }
+}
+
+/// Forward executed by the processor, as reported back to the Authorization contract.
+#[derive(Debug, Clone, Serialize)]
+pub struct Forward {
+    pub execution_id: u64,
+    pub block_number: u64,
+    pub tx_hash: TxHash,
+    pub result: &'static str,
+    pub executed_count: u64,
+    /// Tokens moved from the send account to the deposit account in the same transaction.
+    pub amount: U256,
+}
+
+impl fmt::Display for Forward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>12} {:>10}  {:<20} {:>24}  {}",
+            self.execution_id, self.block_number, self.result, self.amount, self.tx_hash
+        )
+    }
+}
+
+/// Outcome of [`Indexer::sync`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    /// First block scanned, `None` when the index was already at the target block.
+    pub from_block: Option<u64>,
+    pub to_block: u64,
+    pub events: usize,
+    /// Lowest block dropped because the chain reorganized under the index.
+    pub reorged_from: Option<u64>,
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(block) = self.reorged_from {
+            writeln!(f, "Reorg detected, dropped the index from block {block}")?;
+        }
+        match self.from_block {
+            Some(from) => write!(
+                f,
+                "Indexed blocks {from} to {}: {} events",
+                self.to_block, self.events
+            ),
+            None => write!(f, "Index is up to date at block {}", self.to_block),
+        }
+    }
+}
+
+/// Local SQLite history of the events of one program.
+pub struct Indexer {
+    conn: Connection,
+    contracts: ProgramContracts,
+    token: Address,
+    /// Blocks requested per `eth_getLogs` call.
+    pub batch_size: u64,
+    /// Blocks left unindexed below the chain head.
+    pub confirmations: u64,
+}
+
+impl Indexer {
+    /// Opens, or creates, the index at `path`. An index only holds the events of the
+    /// program it was created for.
+    pub fn open(
+        path: impl AsRef<Path>,
+        chain_id: u64,
+        contracts: ProgramContracts,
+        token: Address,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path).step(format!("opening index {}", path.display()))?;
+        conn.execute_batch(SCHEMA).step("creating index schema")?;
+
+        let indexer = Self {
+            conn,
+            contracts,
+            token,
+            batch_size: DEFAULT_BATCH_SIZE,
+            confirmations: 0,
+        };
+        indexer.check_meta("chain_id", &chain_id.to_string())?;
+        indexer.check_meta("authorization", &contracts.authorization.to_string())?;
+
+        Ok(indexer)
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<String>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        Ok(value)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        )?;
+
+        Ok(())
+    }
+
+    fn check_meta(&self, key: &str, expected: &str) -> Result<()> {
+        match self.meta(key)? {
+            Some(value) if value != expected => Err(Error::Config(format!(
+                "the index was built for {key} {value}, not {expected}"
+            ))),
+            Some(_) => Ok(()),
+            None => self.set_meta(key, expected),
+        }
+    }
+
+    /// Last block whose events are in the index.
+    pub fn last_block(&self) -> Result<Option<u64>> {
+        self.meta("last_block")?
+            .map(|value| parse(&value))
+            .transpose()
+    }
+
+    /// Block the index starts at, set by the first [`Indexer::sync`].
+    pub fn start_block(&self) -> Result<Option<u64>> {
+        self.meta("start_block")?
+            .map(|value| parse(&value))
+            .transpose()
+    }
+
+    /// Indexes the blocks after the last indexed one, or from `start_block` on a new index,
+    /// up to the chain head minus [`Indexer::confirmations`]. `start_block` is ignored once
+    /// the index has one.
+    ///
+    /// A reorg under the index is detected from the hash of the last indexed block, the
+    /// index is then rolled back to the highest block still on the chain and indexed again
+    /// from there.
+    pub async fn sync<T, P>(&mut self, rp: &P, start_block: u64) -> Result<SyncReport>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let start_block = match self.start_block()? {
+            Some(block) => block,
+            None => {
+                self.set_meta("start_block", &start_block.to_string())?;
+                start_block
+            }
+        };
+        let reorged_from = self.rollback_reorg(rp, start_block).await?;
+
+        let head = rp.get_block_number().await.step("querying chain head")?;
+        let to_block = head.saturating_sub(self.confirmations);
+        let from_block = match self.last_block()? {
+            Some(last) => last + 1,
+            None => start_block,
+        };
+        if from_block > to_block {
+            return Ok(SyncReport {
+                from_block: None,
+                to_block,
+                events: 0,
+                reorged_from,
+            });
+        }
+
+        let mut events = 0;
+        let mut batch_start = from_block;
+        while batch_start <= to_block {
+            let batch_end = (batch_start + self.batch_size - 1).min(to_block);
+            events += self.index_range(rp, batch_start, batch_end).await?;
+            batch_start = batch_end + 1;
+        }
+        info!("Indexed blocks {from_block} to {to_block}: {events} events");
+
+        Ok(SyncReport {
+            from_block: Some(from_block),
+            to_block,
+            events,
+            reorged_from,
+        })
+    }
+
+    async fn index_range<T, P>(&mut self, rp: &P, from: u64, to: u64) -> Result<usize>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let (logs, checkpoint) = self.fetch_range(rp, from, to).await?;
+
+        let mut indexed = vec![];
+        for log in &logs {
+            let Some(event) = ProgramEvent::decode(log, &self.contracts, self.token) else {
+                continue;
+            };
+            let (Some(block_number), Some(block_hash), Some(tx_hash), Some(log_index)) = (
+                log.block_number,
+                log.block_hash,
+                log.transaction_hash,
+                log.log_index,
+            ) else {
+                return Err(Error::UnexpectedState(format!(
+                    "log of {} in blocks {from} to {to} is pending",
+                    log.address()
+                )));
+            };
+            indexed.push(IndexedEvent {
+                block_number,
+                log_index,
+                block_hash,
+                tx_hash,
+                contract: log.address(),
+                event,
+            });
+        }
+
+        let tx = self.conn.transaction()?;
+        for event in &indexed {
+            let data = serde_json::to_string(&event.event)
+                .map_err(|e| Error::Config(format!("failed to serialize event: {e}")))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO events
+                 (block_number, log_index, block_hash, tx_hash, contract, kind, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    event.block_number as i64,
+                    event.log_index as i64,
+                    event.block_hash.to_string(),
+                    event.tx_hash.to_string(),
+                    event.contract.to_string(),
+                    event.event.kind(),
+                    data
+                ],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO blocks (number, hash) VALUES (?1, ?2)",
+                params![event.block_number as i64, event.block_hash.to_string()],
+            )?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO blocks (number, hash) VALUES (?1, ?2)",
+            params![to as i64, checkpoint.to_string()],
+        )?;
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES ('last_block', ?1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            [to.to_string()],
+        )?;
+        tx.commit()?;
+
+        Ok(indexed.len())
+    }
+
+    /// Logs of blocks `from` to `to` with the hash of `to`, both read from the same chain.
+    ///
+    /// The hash is read before and after the logs, and every log of `to` must carry it: a
+    /// reorg in between would otherwise store logs of the dropped fork under a canonical
+    /// checkpoint, which [`Self::rollback_reorg`] never looks past. The batch is read again
+    /// when they differ.
+    async fn fetch_range<T, P>(&self, rp: &P, from: u64, to: u64) -> Result<(Vec<Log>, B256)>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let filter = Filter::new()
+            .address(vec![
+                self.contracts.authorization,
+                self.contracts.processor,
+                self.contracts.forwarder,
+                self.token,
+            ])
+            .from_block(from)
+            .to_block(to);
+
+        for attempt in 1..=BATCH_ATTEMPTS {
+            let before = block_hash(rp, to).await?;
+            let logs = rp
+                .get_logs(&filter)
+                .await
+                .step(format!("fetching logs of blocks {from} to {to}"))?;
+            let after = block_hash(rp, to).await?.ok_or_else(|| {
+                Error::UnexpectedState(format!("block {to} is not on the chain anymore"))
+            })?;
+
+            let consistent = before == Some(after)
+                && logs
+                    .iter()
+                    .filter(|log| log.block_number == Some(to))
+                    .all(|log| log.block_hash == Some(after));
+            if consistent {
+                return Ok((logs, after));
+            }
+            log::warn!("Blocks {from} to {to} were reorged while reading them (attempt {attempt})");
+        }
+
+        Err(Error::UnexpectedState(format!(
+            "blocks {from} to {to} kept being reorged while reading them"
+        )))
+    }
+
+    /// Drops the blocks that are not on the chain anymore, returning the first dropped one.
+    async fn rollback_reorg<T, P>(&mut self, rp: &P, start_block: u64) -> Result<Option<u64>>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let Some(last) = self.last_block()? else {
+            return Ok(None);
+        };
+
+        let stored = self.stored_block_hashes(last)?;
+        if let Some((_, hash)) = stored.first() {
+            if block_hash(rp, last).await? == Some(*hash) {
+                return Ok(None);
+            }
+        }
+
+        // Blocks are stored from the highest, the first one still on the chain is the fork point
+        let mut common = None;
+        for (number, hash) in &stored {
+            if block_hash(rp, *number).await? == Some(*hash) {
+                common = Some(*number);
+                break;
+            }
+        }
+
+        let tx = self.conn.transaction()?;
+        match common {
+            Some(number) => {
+                tx.execute(
+                    "DELETE FROM events WHERE block_number > ?1",
+                    [number as i64],
+                )?;
+                tx.execute("DELETE FROM blocks WHERE number > ?1", [number as i64])?;
+                tx.execute(
+                    "UPDATE meta SET value = ?1 WHERE key = 'last_block'",
+                    [number.to_string()],
+                )?;
+            }
+            None => {
+                tx.execute("DELETE FROM events", [])?;
+                tx.execute("DELETE FROM blocks", [])?;
+                tx.execute("DELETE FROM meta WHERE key = 'last_block'", [])?;
+            }
+        }
+        tx.commit()?;
+
+        let reorged_from = common.map_or(start_block, |number| number + 1);
+        log::warn!("Chain reorganized under the index, reindexing from block {reorged_from}");
+
+        Ok(Some(reorged_from))
+    }
+
+    fn stored_block_hashes(&self, up_to: u64) -> Result<Vec<(u64, B256)>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT number, hash FROM blocks WHERE number <= ?1 ORDER BY number DESC")?;
+        let rows = statement.query_map([up_to as i64], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut blocks = vec![];
+        for row in rows {
+            let (number, hash) = row?;
+            blocks.push((number as u64, parse(&hash)?));
+        }
+
+        Ok(blocks)
+    }
+
+    /// Indexed events in chain order, only those of `kind` when given (see [`ProgramEvent::kind`]).
+    pub fn events(&self, kind: Option<&str>) -> Result<Vec<IndexedEvent>> {
+        let mut statement = self.conn.prepare(
+            "SELECT block_number, log_index, block_hash, tx_hash, contract, data FROM events
+             WHERE ?1 IS NULL OR kind = ?1
+             ORDER BY block_number, log_index",
+        )?;
+        let rows = statement.query_map([kind], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut events = vec![];
+        for row in rows {
+            let (block_number, log_index, block_hash, tx_hash, contract, data) = row?;
+            events.push(IndexedEvent {
+                block_number: block_number as u64,
+                log_index: log_index as u64,
+                block_hash: parse(&block_hash)?,
+                tx_hash: parse(&tx_hash)?,
+                contract: parse(&contract)?,
+                event: serde_json::from_str(&data)
+                    .map_err(|e| Error::Config(format!("invalid indexed event {data}: {e}")))?,
+            });
+        }
+
+        Ok(events)
+    }
+
+    /// Every forward executed by the processor with the amount it moved and its result.
+    ///
+    /// Forwards are the callbacks received by the Authorization contract, except those of
+    /// Pause and Resume messages.
+    pub fn forwards(&self) -> Result<Vec<Forward>> {
+        let events = self.events(None)?;
+
+        let mut amounts: HashMap<TxHash, U256> = HashMap::new();
+        let mut admin_txs = vec![];
+        for indexed in &events {
+            match &indexed.event {
+                ProgramEvent::Transfer { from, to, value }
+                    if *from == self.contracts.send_account
+                        && *to == self.contracts.deposit_account =>
+                {
+                    *amounts.entry(indexed.tx_hash).or_default() += *value;
+                }
+                ProgramEvent::ProcessorWasPaused | ProgramEvent::ProcessorWasResumed => {
+                    admin_txs.push(indexed.tx_hash);
+                }
+                _ => {}
+            }
+        }
+
+        let forwards = events
+            .iter()
+            .filter(|indexed| !admin_txs.contains(&indexed.tx_hash))
+            .filter_map(|indexed| match &indexed.event {
+                ProgramEvent::CallbackReceived {
+                    execution_id,
+                    execution_result,
+                    executed_count,
+                    ..
+                } => Some(Forward {
+                    execution_id: *execution_id,
+                    block_number: indexed.block_number,
+                    tx_hash: indexed.tx_hash,
+                    result: execution_result_name(*execution_result),
+                    executed_count: *executed_count,
+                    amount: amounts.get(&indexed.tx_hash).copied().unwrap_or_default(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(forwards)
+    }
+}
+
+fn parse<V: std::str::FromStr>(value: &str) -> Result<V>
+where
+    V::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| Error::Config(format!("invalid value {value} in the index: {e}")))
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockHeader {
+    hash: B256,
+}
+
+/// Hash of the canonical block at `number`, `None` if the chain is not that long (anymore).
+async fn block_hash<T, P>(rp: &P, number: u64) -> Result<Option<B256>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let header: Option<BlockHeader> = rp
+        .raw_request(
+            "eth_getBlockByNumber".into(),
+            (format!("{number:#x}"), false),
+        )
+        .await
+        .step(format!("querying block {number}"))?;
+
+    Ok(header.map(|header| header.hash))
+}
+
+/// Logs matching `filter` from `from_block` to the chain head, requested
+/// [`DEFAULT_BATCH_SIZE`] blocks at a time.
+pub async fn get_logs_batched<T, P>(rp: &P, filter: &Filter, from_block: u64) -> Result<Vec<Log>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let to_block = rp.get_block_number().await.step("querying chain head")?;
+
+    let mut logs = vec![];
+    let mut start = from_block;
+    while start <= to_block {
+        let end = (start + DEFAULT_BATCH_SIZE - 1).min(to_block);
+        let batch = filter.clone().from_block(start).to_block(end);
+        logs.extend(
+            rp.get_logs(&batch)
+                .await
+                .step(format!("querying logs of blocks {start} to {end}"))?,
+        );
+        start = end + 1;
+    }
+
+    Ok(logs)
+}
+
+/// First block where `address` has code, found by bisecting `eth_getCode`. Needs a node that
+/// serves historical state, e.g. an archive node or anvil.
+pub async fn deployment_block<T, P>(rp: &P, address: Address) -> Result<u64>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut high = rp.get_block_number().await.step("querying chain head")?;
+    if rp.get_code_at(address).await?.is_empty() {
+        return Err(Error::Config(format!("no contract at {address}")));
+    }
+
+    let mut low = 0;
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let code = rp
+            .get_code_at(address)
+            .block_id(middle.into())
+            .await
+            .step(format!("querying code of {address} at block {middle}"))?;
+        if code.is_empty() {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    Ok(low)
+}
@@ -2,6 +2,25 @@ use alloy::primitives::{address, Address};
 pub const SP1_VERIFIER: &str = "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B";
 pub const FORWARDER: Address = address!("0x7811A1648e43F1bC207d7DF21B039AE9D2870b91");
 pub const AUTHORIZATION: Address = address!("0x84F36aeF81aBf1E34bcA9e470fE15e12697CB7Fd");
+pub const TOKEN: Address = address!("0xeC67cF0755c0A5aaD6C4A4235fDfA35c1EFEA6A9");
 pub const COPROCESSOR_APP_ID: &str =
     "12795336697c556ece48a2879e7cb1ddc679ee7053d5059850b354257bc2849a";
+pub mod admin;
+pub mod artifacts;
+pub mod coprocessor;
+pub mod create2;
+pub mod deploy;
+pub mod error;
+pub mod gas;
+pub mod gateway;
+pub mod indexer;
+pub mod logging;
+pub mod manifest;
+pub mod metrics;
+pub mod plan;
+pub mod program;
+pub mod safe;
+pub mod signer;
+pub mod status;
 pub mod types;
+pub mod vk;
@@ -0,0 +1,59 @@
+use std::{fmt, str::FromStr};
+
+use tracing_subscriber::EnvFilter;
+
+use crate::error::{Error, Result};
+
+/// Format of the logs written to stderr by the binaries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable lines, with the fields of the enclosing spans as a prefix.
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the current span and its parents.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::Config(format!(
+                "unknown log format {format}, expected text or json"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Installs the global subscriber, filtered by `RUST_LOG` (`info` by default).
+///
+/// Records of the `log` crate, used by the dependencies and most of this crate, are
+/// forwarded to it along with the spans and events of `tracing`.
+pub fn init(format: LogFormat) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+
+    match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .try_init(),
+    }
+    .map_err(|e| Error::Config(format!("failed to initialize logging: {e}")))
+}
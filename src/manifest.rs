@@ -0,0 +1,209 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    primitives::{Address, FixedBytes},
+    providers::Provider,
+    transports::Transport,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    admin::ProgramContracts,
+    create2::Create2Config,
+    error::{Context, Error, Result},
+    gateway,
+    indexer::deployment_block,
+    types::sol_types::SP1VerificationGateway,
+    AUTHORIZATION, COPROCESSOR_APP_ID, FORWARDER, TOKEN,
+};
+
+/// Help of the `--manifest` argument of the binaries.
+pub const MANIFEST_HELP: &str = "Deployment manifest written by `valence-demo deploy`. \
+    If it doesn't exist, the addresses recorded in src/lib.rs are used instead";
+
+/// Where the SP1 verifier recorded in a manifest comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerifierOrigin {
+    /// Already deployed on the chain.
+    Existing,
+    /// Mock verifier accepting any proof.
+    Mock,
+    /// Deployed from the artifact at this path.
+    Artifact(PathBuf),
+}
+
+impl fmt::Display for VerifierOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierOrigin::Existing => write!(f, "existing"),
+            VerifierOrigin::Mock => write!(f, "mock, accepts any proof"),
+            VerifierOrigin::Artifact(path) => write!(f, "deployed from {}", path.display()),
+        }
+    }
+}
+
+/// Everything needed to talk to a deployed program, written by `deploy` and
+/// read back by every other command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentManifest {
+    pub chain_id: u64,
+    #[serde(flatten)]
+    pub contracts: ProgramContracts,
+    pub verification_gateway_implementation: Address,
+    pub sp1_verifier: Address,
+    /// Whether the SP1 verifier was already on the chain or deployed along with the program.
+    #[serde(default)]
+    pub sp1_verifier_origin: Option<VerifierOrigin>,
+    pub token: Address,
+    pub coprocessor_app_id: Option<String>,
+    /// Coprocessor the domain VK of the verification gateway was taken from.
+    #[serde(default)]
+    pub coprocessor_url: Option<String>,
+    /// Domain VK the verification gateway was initialized with, or last rotated to.
+    #[serde(default)]
+    pub domain_vk: Option<FixedBytes<32>>,
+    /// Factory and salt, when the contracts were deployed with CREATE2.
+    #[serde(default)]
+    pub create2: Option<Create2Config>,
+    /// Owner the contracts were handed to at the end of the deployment, `None` when the
+    /// deployer kept them.
+    #[serde(default)]
+    pub owner: Option<Address>,
+    /// Whether the Forwarder was handed to the processor instead of `owner`.
+    #[serde(default)]
+    pub processor_owns_forwarder: bool,
+    /// Chain head when the deployment started, no program event is older.
+    #[serde(default)]
+    pub deployment_block: Option<u64>,
+}
+
+impl DeploymentManifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!("failed to read manifest {}: {e}", path.display()))
+        })?;
+
+        serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("invalid manifest {}: {e}", path.display())))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("failed to serialize manifest: {e}")))?;
+
+        fs::write(path, contents)
+            .map_err(|e| Error::Config(format!("failed to write manifest {}: {e}", path.display())))
+    }
+
+    /// Loads the manifest at `path`, or builds one from the addresses recorded in
+    /// `src/lib.rs` when no manifest has been written yet.
+    pub async fn load_or_discover<T, P>(path: impl AsRef<Path>, rp: &P) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        if path.as_ref().exists() {
+            return Self::load(path);
+        }
+
+        let contracts = ProgramContracts::discover(rp, AUTHORIZATION, FORWARDER).await?;
+        let sp1_verifier = SP1VerificationGateway::new(contracts.verification_gateway, rp)
+            .verifier()
+            .call()
+            .await?
+            ._0;
+
+        Ok(Self {
+            chain_id: rp.get_chain_id().await?,
+            contracts,
+            verification_gateway_implementation: gateway::implementation(
+                rp,
+                contracts.verification_gateway,
+            )
+            .await?,
+            sp1_verifier,
+            sp1_verifier_origin: Some(VerifierOrigin::Existing),
+            token: TOKEN,
+            coprocessor_app_id: Some(COPROCESSOR_APP_ID.to_string()),
+            coprocessor_url: None,
+            domain_vk: None,
+            create2: None,
+            owner: None,
+            processor_owns_forwarder: false,
+            deployment_block: None,
+        })
+    }
+
+    /// First block to replay the program events from: the recorded deployment block, or the
+    /// block the send account, deployed first, got its code at when it wasn't recorded.
+    pub async fn first_block<T, P>(&self, rp: &P) -> Result<u64>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        match self.deployment_block {
+            Some(block) => Ok(block),
+            None => deployment_block(rp, self.contracts.send_account)
+                .await
+                .step("finding the deployment block"),
+        }
+    }
+}
+
+impl fmt::Display for DeploymentManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Chain id:                 {}", self.chain_id)?;
+        writeln!(
+            f,
+            "Send account:             {}",
+            self.contracts.send_account
+        )?;
+        writeln!(
+            f,
+            "Deposit account:          {}",
+            self.contracts.deposit_account
+        )?;
+        writeln!(f, "DEMO token:               {}", self.token)?;
+        writeln!(f, "Processor:                {}", self.contracts.processor)?;
+        writeln!(f, "Forwarder:                {}", self.contracts.forwarder)?;
+        writeln!(
+            f,
+            "Authorization:            {}",
+            self.contracts.authorization
+        )?;
+        writeln!(
+            f,
+            "Verification Gateway:     {} (implementation {})",
+            self.contracts.verification_gateway, self.verification_gateway_implementation
+        )?;
+        match &self.sp1_verifier_origin {
+            Some(origin) => writeln!(
+                f,
+                "SP1 verifier:             {} ({origin})",
+                self.sp1_verifier
+            )?,
+            None => writeln!(f, "SP1 verifier:             {}", self.sp1_verifier)?,
+        }
+        if let Some(owner) = self.owner {
+            writeln!(f, "Owner:                    {owner}")?;
+        }
+        writeln!(
+            f,
+            "Coprocessor:              {}",
+            self.coprocessor_url.as_deref().unwrap_or("unknown")
+        )?;
+        write!(
+            f,
+            "Coprocessor app id:       {}",
+            self.coprocessor_app_id
+                .as_deref()
+                .unwrap_or(COPROCESSOR_APP_ID)
+        )
+    }
+}
@@ -0,0 +1,273 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent,
+    transports::Transport,
+};
+use log::info;
+
+use crate::{
+    admin::ProgramContracts,
+    coprocessor::ProofTiming,
+    error::Result,
+    indexer::{execution_result_name, DEFAULT_BATCH_SIZE},
+    manifest::DeploymentManifest,
+    types::sol_types::{processor_contract::LiteProcessor, Authorization, Forwarder, MockERC20},
+};
+
+/// Results a callback can report, exported even before the first callback so that
+/// alerts on their rate have a series to work with.
+const CALLBACK_RESULTS: [&str; 3] = ["success", "rejected", "partially-executed"];
+
+/// Balance of one of the program accounts in one token.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBalance {
+    pub account: &'static str,
+    pub address: Address,
+    pub token: Address,
+    pub balance: U256,
+}
+
+/// A scrape of the program, rendered in the Prometheus text format by its `Display`.
+#[derive(Debug, Clone)]
+pub struct ProgramMetrics {
+    pub block_number: u64,
+    pub balances: Vec<TokenBalance>,
+    pub paused: bool,
+    /// Zero if the forwarder never ran.
+    pub last_execution_block: u64,
+    pub callbacks: BTreeMap<&'static str, u64>,
+    pub proof_timing: Option<ProofTiming>,
+}
+
+impl ProgramMetrics {
+    /// `None` until the first forward.
+    pub fn blocks_since_last_execution(&self) -> Option<u64> {
+        (self.last_execution_block > 0)
+            .then(|| self.block_number.saturating_sub(self.last_execution_block))
+    }
+}
+
+/// Reads the program metrics on every scrape, counting callbacks incrementally.
+///
+/// Callbacks are counted from the logs of the blocks mined since the previous scrape, so
+/// the counts of a reorged callback aren't taken back.
+pub struct MetricsCollector {
+    contracts: ProgramContracts,
+    tokens: Vec<Address>,
+    proof_timing: Option<PathBuf>,
+    next_block: u64,
+    callbacks: BTreeMap<&'static str, u64>,
+    pub batch_size: u64,
+}
+
+impl MetricsCollector {
+    /// Collects the balances of the manifest token and of `tokens`, counting the callbacks
+    /// from `start_block` on. The proof latency is read from `proof_timing`, as written by
+    /// `valence-demo activate --zk --proof-timing`.
+    pub fn new(
+        manifest: &DeploymentManifest,
+        tokens: &[Address],
+        proof_timing: Option<PathBuf>,
+        start_block: u64,
+    ) -> Self {
+        let mut all_tokens = vec![manifest.token];
+        for token in tokens {
+            if !all_tokens.contains(token) {
+                all_tokens.push(*token);
+            }
+        }
+
+        Self {
+            contracts: manifest.contracts,
+            tokens: all_tokens,
+            proof_timing,
+            next_block: start_block,
+            callbacks: CALLBACK_RESULTS.iter().map(|result| (*result, 0)).collect(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub async fn collect<T, P>(&mut self, rp: &P) -> Result<ProgramMetrics>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let block_number = rp.get_block_number().await?;
+        self.count_callbacks(rp, block_number).await?;
+
+        let mut balances = vec![];
+        for token in &self.tokens {
+            let contract = MockERC20::new(*token, rp);
+            for (account, address) in [
+                ("send", self.contracts.send_account),
+                ("deposit", self.contracts.deposit_account),
+            ] {
+                balances.push(TokenBalance {
+                    account,
+                    address,
+                    token: *token,
+                    balance: contract.balanceOf(address).call().await?._0,
+                });
+            }
+        }
+
+        let proof_timing = match &self.proof_timing {
+            Some(path) => ProofTiming::load(path)?,
+            None => None,
+        };
+
+        Ok(ProgramMetrics {
+            block_number,
+            balances,
+            paused: LiteProcessor::new(self.contracts.processor, rp)
+                .paused()
+                .call()
+                .await?
+                ._0,
+            last_execution_block: Forwarder::new(self.contracts.forwarder, rp)
+                .lastExecution()
+                .call()
+                .await?
+                .blockHeight,
+            callbacks: self.callbacks.clone(),
+            proof_timing,
+        })
+    }
+
+    async fn count_callbacks<T, P>(&mut self, rp: &P, to_block: u64) -> Result<()>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let from_block = self.next_block;
+        while self.next_block <= to_block {
+            let batch_end = (self.next_block + self.batch_size.max(1) - 1).min(to_block);
+            let filter = Filter::new()
+                .address(self.contracts.authorization)
+                .event_signature(Authorization::CallbackReceived::SIGNATURE_HASH)
+                .from_block(self.next_block)
+                .to_block(batch_end);
+
+            for log in rp.get_logs(&filter).await? {
+                let event = log.log_decode::<Authorization::CallbackReceived>()?;
+                let result = execution_result_name(u8::from(event.inner.data.executionResult));
+                *self.callbacks.entry(result).or_default() += 1;
+            }
+            self.next_block = batch_end + 1;
+        }
+        if from_block <= to_block {
+            info!("Counted callbacks of blocks {from_block} to {to_block}");
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ProgramMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        header(
+            f,
+            "valence_account_balance",
+            "gauge",
+            "Token balance of a program account",
+        )?;
+        for balance in &self.balances {
+            writeln!(
+                f,
+                "valence_account_balance{{account=\"{}\",address=\"{}\",token=\"{}\"}} {}",
+                balance.account, balance.address, balance.token, balance.balance
+            )?;
+        }
+
+        header(
+            f,
+            "valence_block_number",
+            "gauge",
+            "Latest block of the node",
+        )?;
+        writeln!(f, "valence_block_number {}", self.block_number)?;
+
+        header(
+            f,
+            "valence_forwarder_last_execution_block",
+            "gauge",
+            "Block of the last forward, 0 if it never ran",
+        )?;
+        writeln!(
+            f,
+            "valence_forwarder_last_execution_block {}",
+            self.last_execution_block
+        )?;
+        if let Some(blocks) = self.blocks_since_last_execution() {
+            header(
+                f,
+                "valence_forwarder_blocks_since_last_execution",
+                "gauge",
+                "Blocks mined since the last forward",
+            )?;
+            writeln!(f, "valence_forwarder_blocks_since_last_execution {blocks}")?;
+        }
+
+        header(
+            f,
+            "valence_processor_paused",
+            "gauge",
+            "1 if the processor is paused",
+        )?;
+        writeln!(f, "valence_processor_paused {}", u8::from(self.paused))?;
+
+        header(
+            f,
+            "valence_callbacks_total",
+            "counter",
+            "Callbacks received by the Authorization contract, by execution result",
+        )?;
+        for (result, count) in &self.callbacks {
+            writeln!(f, "valence_callbacks_total{{result=\"{result}\"}} {count}")?;
+        }
+
+        if let Some(timing) = &self.proof_timing {
+            let app_id = escape_label(&timing.app_id);
+            header(
+                f,
+                "valence_coprocessor_proof_seconds",
+                "gauge",
+                "Time the coprocessor took to generate the last proof",
+            )?;
+            writeln!(
+                f,
+                "valence_coprocessor_proof_seconds{{app_id=\"{app_id}\"}} {}",
+                timing.seconds
+            )?;
+            header(
+                f,
+                "valence_coprocessor_last_proof_timestamp_seconds",
+                "gauge",
+                "Unix time the last proof was received",
+            )?;
+            writeln!(
+                f,
+                "valence_coprocessor_last_proof_timestamp_seconds{{app_id=\"{app_id}\"}} {}",
+                timing.finished_at
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn header(f: &mut fmt::Formatter<'_>, name: &str, kind: &str, help: &str) -> fmt::Result {
+    writeln!(f, "# HELP {name} {help}")?;
+    writeln!(f, "# TYPE {name} {kind}")
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
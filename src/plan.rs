@@ -0,0 +1,403 @@
+use std::{fmt, fs, path::Path};
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{Address, Bytes, FixedBytes},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    sol_types::{SolCall, SolValue},
+    transports::Transport,
+};
+use serde::Serialize;
+
+use crate::{
+    admin::ProgramContracts,
+    create2::{self, Create2Config, PlannedContract},
+    deploy::{
+        expected_owners, forwarder_config, resolve_verifier, transfer_step, DeployConfig,
+        ResolvedVerifier,
+    },
+    error::{Context, Error, Result},
+    manifest::DeploymentManifest,
+    types::sol_types::{
+        processor_contract::LiteProcessor, Authorization, BaseAccount, ERC1967Proxy, Forwarder,
+        MockERC20, SP1VerificationGateway,
+    },
+};
+
+/// Unsigned transaction of a [`DeployPlan`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedTransaction {
+    pub step: String,
+    /// Address of the contract created by the transaction, predicted from the nonce or the
+    /// CREATE2 salt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+    /// Transaction in the JSON-RPC format, with `gas` set when it could be estimated.
+    pub transaction: TransactionRequest,
+    /// Why the gas could not be estimated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_estimate_error: Option<String>,
+}
+
+/// Every transaction [`crate::deploy::deploy_program`] sends, built without signing anything.
+/// The deployment sends the transactions of its own plan, so both can't drift apart.
+///
+/// Contract addresses are predicted from the nonce of the deployer, or from the salt for the
+/// contracts deployed through the CREATE2 factory, so the plan only holds if no other
+/// transaction is sent from the deployer before the deployment.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployPlan {
+    pub chain_id: u64,
+    pub from: Address,
+    /// Addresses the deployment will have if executed as planned.
+    pub predicted: DeploymentManifest,
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+impl DeployPlan {
+    /// Plans the deployment of `config` from `from`, starting at its current nonce, and
+    /// estimates the gas of the transactions that can be.
+    pub async fn new<T, P>(rp: &P, from: Address, config: &DeployConfig) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let mut plan = Self::build(rp, from, config).await?;
+        plan.estimate_gas(rp).await;
+
+        Ok(plan)
+    }
+
+    /// Plans the deployment of `config` from `from`, leaving the gas to the signer.
+    pub(crate) async fn build<T, P>(rp: &P, from: Address, config: &DeployConfig) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let chain_id = rp.get_chain_id().await.step("querying chain id")?;
+        // No program event can be older than the chain head before the first transaction
+        let deployment_block = rp.get_block_number().await.step("querying chain head")?;
+        let nonce = rp
+            .get_transaction_count(from)
+            .await
+            .step("querying deployer nonce")?;
+        let mut builder = PlanBuilder {
+            chain_id,
+            from,
+            nonce,
+            transactions: Vec::new(),
+        };
+
+        let sp1_verifier = match resolve_verifier(rp, &config.verifier).await? {
+            ResolvedVerifier::Existing(address) => address,
+            ResolvedVerifier::Deploy(init_code) => builder.create(
+                "deploying SP1 verifier",
+                TransactionRequest::default().with_deploy_code(init_code),
+            ),
+        };
+
+        let program = match config.create2 {
+            Some(create2) => {
+                create2::plan_program(rp, &mut builder, config, create2, sp1_verifier).await?
+            }
+            None => plan_program(rp, &mut builder, config, sp1_verifier),
+        };
+
+        let predicted = DeploymentManifest {
+            chain_id,
+            contracts: program.contracts,
+            verification_gateway_implementation: program.verification_gateway_implementation,
+            sp1_verifier,
+            sp1_verifier_origin: Some(config.verifier.origin()),
+            token: program.token,
+            coprocessor_app_id: None,
+            coprocessor_url: None,
+            domain_vk: Some(config.domain_vk),
+            create2: config.create2,
+            owner: config.owner,
+            processor_owns_forwarder: config.processor_owns_forwarder,
+            deployment_block: Some(deployment_block),
+        };
+        for (contract, new_owner) in expected_owners(&predicted, from) {
+            if new_owner != from {
+                let address = predicted.contracts.address_of(contract);
+                builder.call(
+                    transfer_step(contract),
+                    contract.transfer_ownership_tx(rp, address, new_owner),
+                );
+            }
+        }
+
+        Ok(Self {
+            chain_id,
+            from,
+            predicted,
+            transactions: builder.transactions,
+        })
+    }
+
+    /// Estimates the gas of the transactions that don't depend on an earlier one.
+    ///
+    /// Calls to contracts created by the plan can't be estimated against the current state,
+    /// the node would price them as plain transfers. Run `deploy` on a fork of the chain
+    /// for the gas of the whole deployment.
+    async fn estimate_gas<T, P>(&mut self, rp: &P)
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let created: Vec<Address> = self
+            .transactions
+            .iter()
+            .filter_map(|planned| planned.contract_address)
+            .collect();
+
+        for planned in &mut self.transactions {
+            if let Some(to) = planned.transaction.to.and_then(|kind| kind.to().copied()) {
+                if created.contains(&to) {
+                    planned.gas_estimate_error =
+                        Some(format!("calls {to}, which is created by the plan"));
+                    continue;
+                }
+            }
+
+            match rp.estimate_gas(&planned.transaction).await {
+                Ok(gas) => planned.transaction.set_gas_limit(gas),
+                Err(e) => planned.gas_estimate_error = Some(e.to_string()),
+            }
+        }
+    }
+
+    /// Sum of the gas estimates, `None` unless every transaction could be estimated.
+    pub fn estimated_gas(&self) -> Option<u64> {
+        self.transactions
+            .iter()
+            .map(|planned| planned.transaction.gas)
+            .sum()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("failed to serialize deploy plan: {e}")))?;
+        fs::write(path, json)
+            .map_err(|e| Error::Config(format!("failed to write {}: {e}", path.display())))
+    }
+}
+
+impl fmt::Display for DeployPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Deployment plan for chain {} from {}",
+            self.chain_id, self.from
+        )?;
+        writeln!(
+            f,
+            "{:>6}  {:<48} {:<42} {:>12}",
+            "Nonce", "Step", "Contract", "Gas estimate"
+        )?;
+        for planned in &self.transactions {
+            let contract = planned
+                .contract_address
+                .map(|address| address.to_string())
+                .unwrap_or_default();
+            let gas = planned
+                .transaction
+                .gas
+                .map(|gas| gas.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:>6}  {:<48} {:<42} {:>12}",
+                planned.transaction.nonce.unwrap_or_default(),
+                planned.step,
+                contract,
+                gas
+            )?;
+        }
+
+        match self.estimated_gas() {
+            Some(total) => write!(f, "Estimated gas: {total}"),
+            None => write!(
+                f,
+                "Estimated gas: unavailable, some transactions could not be estimated \
+                 (run `deploy` on a fork of the chain for the gas of the whole deployment)"
+            ),
+        }
+    }
+}
+
+/// Contracts a [`PlanBuilder`] deploys for a program, before ownership is handed over.
+pub(crate) struct PlannedProgram {
+    pub contracts: ProgramContracts,
+    pub verification_gateway_implementation: Address,
+    pub token: Address,
+}
+
+/// Calldata initializing the verification gateway proxy in its constructor, so nobody can
+/// initialize a deployed but uninitialized proxy first.
+pub(crate) fn gateway_initialize_data(sp1_verifier: Address, domain_vk: FixedBytes<32>) -> Bytes {
+    SP1VerificationGateway::initializeCall {
+        _verifier: sp1_verifier,
+        _domainVK: domain_vk,
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Deploys and wires every contract of the program from the deployer nonce.
+fn plan_program<T, P>(
+    rp: &P,
+    builder: &mut PlanBuilder,
+    config: &DeployConfig,
+    sp1_verifier: Address,
+) -> PlannedProgram
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let from = builder.from;
+    let send_account = builder.create(
+        "deploying send account",
+        BaseAccount::deploy_builder(rp, from, vec![]).into_transaction_request(),
+    );
+    let deposit_account = builder.create(
+        "deploying deposit account",
+        BaseAccount::deploy_builder(rp, from, vec![]).into_transaction_request(),
+    );
+    let processor = builder.create(
+        "deploying processor",
+        LiteProcessor::deploy_builder(rp, FixedBytes::<32>::default(), Address::ZERO, 0, vec![])
+            .into_transaction_request(),
+    );
+    let token = builder.create(
+        "deploying DEMO token",
+        MockERC20::deploy_builder(rp, "Demo Token".to_string(), "DEMO".to_string(), 18)
+            .into_transaction_request(),
+    );
+    builder.call(
+        "minting DEMO to send account",
+        MockERC20::new(token, rp)
+            .mint(send_account, config.mint_amount)
+            .into_transaction_request(),
+    );
+
+    let forwarder_config = forwarder_config(
+        send_account,
+        deposit_account,
+        token,
+        config.max_forward_amount,
+    );
+    let forwarder = builder.create(
+        "deploying forwarder library",
+        Forwarder::deploy_builder(rp, from, processor, forwarder_config.abi_encode().into())
+            .into_transaction_request(),
+    );
+    builder.call(
+        "approving forwarder library on send account",
+        BaseAccount::new(send_account, rp)
+            .approveLibrary(forwarder)
+            .into_transaction_request(),
+    );
+
+    let verification_gateway_implementation = builder.create(
+        "deploying verification gateway implementation",
+        SP1VerificationGateway::deploy_builder(rp).into_transaction_request(),
+    );
+    let verification_gateway = builder.create(
+        "deploying verification gateway proxy",
+        ERC1967Proxy::deploy_builder(
+            rp,
+            verification_gateway_implementation,
+            gateway_initialize_data(sp1_verifier, config.domain_vk),
+        )
+        .into_transaction_request(),
+    );
+
+    // The deployer owns everything while wiring, ownership is handed over at the end
+    let authorization = builder.create(
+        "deploying authorization",
+        Authorization::deploy_builder(
+            rp,
+            from,
+            processor,
+            verification_gateway,
+            true, // Store callbacks
+        )
+        .into_transaction_request(),
+    );
+    builder.call(
+        "adding authorization to processor",
+        LiteProcessor::new(processor, rp)
+            .addAuthorizedAddress(authorization)
+            .into_transaction_request(),
+    );
+
+    PlannedProgram {
+        contracts: ProgramContracts {
+            authorization,
+            processor,
+            forwarder,
+            verification_gateway,
+            send_account,
+            deposit_account,
+        },
+        verification_gateway_implementation,
+        token,
+    }
+}
+
+/// Numbers the transactions of a plan and predicts the addresses of the contracts they create.
+pub(crate) struct PlanBuilder {
+    chain_id: u64,
+    pub from: Address,
+    nonce: u64,
+    transactions: Vec<PlannedTransaction>,
+}
+
+impl PlanBuilder {
+    fn push(&mut self, step: String, tx: TransactionRequest, contract_address: Option<Address>) {
+        let transaction = tx
+            .with_from(self.from)
+            .with_nonce(self.nonce)
+            .with_chain_id(self.chain_id);
+        self.transactions.push(PlannedTransaction {
+            step,
+            contract_address,
+            transaction,
+            gas_estimate_error: None,
+        });
+        self.nonce += 1;
+    }
+
+    pub fn create(&mut self, step: &str, tx: TransactionRequest) -> Address {
+        let address = self.from.create(self.nonce);
+        self.push(step.to_string(), tx, Some(address));
+
+        address
+    }
+
+    /// Deploys `contract` through the CREATE2 factory of `create2`.
+    pub fn create2(&mut self, create2: &Create2Config, contract: &PlannedContract) -> Address {
+        let calldata = [
+            create2.salt_for(contract.label).as_slice(),
+            &contract.init_code[..],
+        ]
+        .concat();
+        let tx = TransactionRequest::default()
+            .with_to(create2.factory)
+            .with_input(calldata);
+        self.push(
+            format!("deploying {} through the CREATE2 factory", contract.label),
+            tx,
+            Some(contract.address),
+        );
+
+        contract.address
+    }
+
+    pub fn call(&mut self, step: impl Into<String>, tx: TransactionRequest) {
+        self.push(step.into(), tx, None);
+    }
+}
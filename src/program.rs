@@ -0,0 +1,277 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{Address, Bytes, FixedBytes, TxHash, U256},
+    providers::Provider,
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    sol_types::{SolCall, SolValue},
+    transports::Transport,
+};
+use serde::Serialize;
+use valence_domain_clients::{
+    clients::ethereum::EthereumClient,
+    evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
+};
+
+use crate::{
+    admin::{set_processor_paused, ControlReport, ProgramContracts},
+    error::{Error, Result},
+    indexer::deployment_block,
+    manifest::DeploymentManifest,
+    types::{
+        processor_messages::{forwardCall, forward_message},
+        sol_types::{Authorization, MockERC20},
+    },
+};
+
+/// Label of the standard authorization that allows anyone to trigger the forwarder.
+pub const FORWARD_LABEL: &str = "forward";
+
+/// Registry the coprocessor circuit generates its `ZkMessage` for.
+pub const ZK_REGISTRY: u64 = 0;
+
+/// Adds the [`FORWARD_LABEL`] standard authorization, open to any address, that allows
+/// calling `forward()` on the Forwarder library.
+pub fn authorize_forward_tx<T, P>(rp: &P, contracts: &ProgramContracts) -> TransactionRequest
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Authorization::new(contracts.authorization, rp)
+        .addStandardAuthorizations(
+            vec![FORWARD_LABEL.to_string()],
+            vec![vec![Address::ZERO]],
+            vec![vec![Authorization::AuthorizationData {
+                contractAddress: contracts.forwarder,
+                useFunctionSelector: true,
+                functionSelector: FixedBytes::<4>::new(forwardCall::SELECTOR),
+                callHash: FixedBytes::<32>::default(),
+            }]],
+        )
+        .into_transaction_request()
+}
+
+/// Allows `executor` to execute ZK messages for [`ZK_REGISTRY`] proven against the
+/// program verification key `vk`.
+pub fn register_zk_registry_tx<T, P>(
+    rp: &P,
+    contracts: &ProgramContracts,
+    executor: Address,
+    vk: FixedBytes<32>,
+) -> TransactionRequest
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    // Remember we send arrays because we allow multiple registries added at once
+    Authorization::new(contracts.authorization, rp)
+        .addRegistries(
+            vec![ZK_REGISTRY],
+            vec![vec![executor]],
+            vec![vk],
+            vec![false],
+        )
+        .into_transaction_request()
+}
+
+/// Proof returned by the coprocessor, split into the program and domain parts
+/// expected by `Authorization.executeZKMessage`.
+#[derive(Debug, Clone)]
+pub struct ZkProof {
+    pub program_inputs: Bytes,
+    pub program_proof: Bytes,
+    pub domain_inputs: Bytes,
+    pub domain_proof: Bytes,
+}
+
+/// Token balances of the two program accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Balances {
+    pub send_account: U256,
+    pub deposit_account: U256,
+}
+
+impl Balances {
+    /// Reads the `token` balances of the accounts of `contracts`, without a signer.
+    pub async fn load<T, P>(rp: &P, contracts: &ProgramContracts, token: Address) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let token = MockERC20::new(token, rp);
+
+        Ok(Self {
+            send_account: token.balanceOf(contracts.send_account).call().await?._0,
+            deposit_account: token.balanceOf(contracts.deposit_account).call().await?._0,
+        })
+    }
+}
+
+impl fmt::Display for Balances {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Send account balance:    {}", self.send_account)?;
+        write!(f, "Deposit account balance: {}", self.deposit_account)
+    }
+}
+
+/// High level client for a deployed instance of the program.
+///
+/// Wraps the signer and every contract address so the forwarding flows can be
+/// embedded in other services instead of going through the demo binaries.
+pub struct ValenceProgram {
+    client: EthereumClient,
+    contracts: ProgramContracts,
+    token: Address,
+}
+
+impl ValenceProgram {
+    pub fn new(client: EthereumClient, contracts: ProgramContracts, token: Address) -> Self {
+        Self {
+            client,
+            contracts,
+            token,
+        }
+    }
+
+    /// Builds the client from the Authorization and Forwarder addresses, reading
+    /// the rest of the program configuration from chain.
+    pub async fn connect(
+        client: EthereumClient,
+        authorization: Address,
+        forwarder: Address,
+        token: Address,
+    ) -> Result<Self> {
+        let rp = client.get_request_provider().await.map_err(Error::rpc)?;
+        let contracts = ProgramContracts::discover(&rp, authorization, forwarder).await?;
+
+        Ok(Self::new(client, contracts, token))
+    }
+
+    pub fn from_manifest(client: EthereumClient, manifest: &DeploymentManifest) -> Self {
+        Self::new(client, manifest.contracts, manifest.token)
+    }
+
+    pub fn client(&self) -> &EthereumClient {
+        &self.client
+    }
+
+    pub fn contracts(&self) -> &ProgramContracts {
+        &self.contracts
+    }
+
+    pub fn token(&self) -> Address {
+        self.token
+    }
+
+    /// Adds the `forward` standard authorization, open to any address, that allows
+    /// calling `forward()` on the Forwarder library.
+    pub async fn authorize_forward(&self) -> Result<TransactionReceipt> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+
+        let tx = authorize_forward_tx(&rp, &self.contracts);
+
+        self.client.sign_and_send(tx).await.map_err(Error::signing)
+    }
+
+    /// Triggers the forwarder through the `forward` standard authorization.
+    pub async fn forward_via_standard_auth(&self) -> Result<TransactionReceipt> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+        let authorization = Authorization::new(self.contracts.authorization, &rp);
+
+        let execution_id = authorization.executionId().call().await?._0;
+        let processor_message = forward_message(self.contracts.forwarder, execution_id);
+
+        let tx = authorization
+            .sendProcessorMessage(
+                FORWARD_LABEL.to_string(),
+                Bytes::from(processor_message.abi_encode()),
+            )
+            .into_transaction_request();
+
+        self.client.sign_and_send(tx).await.map_err(Error::signing)
+    }
+
+    /// Allows the signer to execute ZK messages for [`ZK_REGISTRY`] proven against
+    /// the given program verification key.
+    pub async fn register_zk_registry(&self, vk: FixedBytes<32>) -> Result<TransactionReceipt> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+
+        let tx = register_zk_registry_tx(&rp, &self.contracts, self.client.signer().address(), vk);
+
+        self.client.sign_and_send(tx).await.map_err(Error::signing)
+    }
+
+    /// Submits a coprocessor proof to `executeZKMessage`.
+    pub async fn forward_via_zk(&self, proof: ZkProof) -> Result<TransactionReceipt> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+        let authorization = Authorization::new(self.contracts.authorization, &rp);
+
+        let tx = authorization
+            .executeZKMessage(
+                proof.program_inputs,
+                proof.program_proof,
+                proof.domain_inputs,
+                proof.domain_proof,
+            )
+            .into_transaction_request();
+
+        self.client.sign_and_send(tx).await.map_err(Error::signing)
+    }
+
+    pub async fn balances(&self) -> Result<Balances> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+
+        Balances::load(&rp, &self.contracts, self.token).await
+    }
+
+    /// Pauses the processor. Returns `None` if it was already paused.
+    pub async fn pause(&self) -> Result<Option<TxHash>> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+        set_processor_paused(&self.client, &rp, &self.contracts, true).await
+    }
+
+    /// Resumes the processor. Returns `None` if it was not paused.
+    pub async fn resume(&self) -> Result<Option<TxHash>> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+        set_processor_paused(&self.client, &rp, &self.contracts, false).await
+    }
+
+    pub async fn control_report(&self) -> Result<ControlReport> {
+        let rp = self
+            .client
+            .get_request_provider()
+            .await
+            .map_err(Error::rpc)?;
+        // The send account is deployed first, no control event is older
+        let from_block = deployment_block(&rp, self.contracts.send_account).await?;
+        ControlReport::load(&rp, &self.contracts, from_block).await
+    }
+}
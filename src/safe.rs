@@ -0,0 +1,148 @@
+use std::{
+    fmt, fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    primitives::{Address, Bytes, U256},
+    rpc::types::TransactionRequest,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Batch file format of the Safe{Wallet} Transaction Builder app.
+pub const SAFE_BATCH_VERSION: &str = "1.0";
+
+/// Calls to be proposed from a Safe, in the JSON format the Transaction Builder app imports.
+///
+/// Used when the program contracts are owned by a multisig, so admin calls are written out
+/// instead of being signed by a single key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeBatch {
+    pub version: String,
+    /// Decimal chain id, the Transaction Builder compares it with the Safe's chain.
+    pub chain_id: String,
+    /// Creation time in milliseconds since the epoch.
+    pub created_at: u64,
+    pub meta: SafeBatchMeta,
+    pub transactions: Vec<SafeTransaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeBatchMeta {
+    pub name: String,
+    pub description: String,
+    pub created_from_safe_address: Address,
+}
+
+/// Single call of a [`SafeBatch`], given as raw calldata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeTransaction {
+    pub to: Address,
+    /// Decimal amount of wei sent with the call.
+    pub value: String,
+    pub data: Bytes,
+    /// Only set for calls built in the app from an ABI, `data` is used otherwise.
+    pub contract_method: Option<serde_json::Value>,
+    pub contract_inputs_values: Option<serde_json::Value>,
+}
+
+impl SafeBatch {
+    pub fn new(chain_id: u64, safe: Address) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            version: SAFE_BATCH_VERSION.to_string(),
+            chain_id: chain_id.to_string(),
+            created_at,
+            meta: SafeBatchMeta {
+                name: "Valence program administration".to_string(),
+                description: String::new(),
+                created_from_safe_address: safe,
+            },
+            transactions: vec![],
+        }
+    }
+
+    /// Loads the batch at `path` to add calls to it, or starts a new one if there is none.
+    pub fn load_or_new(path: &Path, chain_id: u64, safe: Address) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(chain_id, safe));
+        }
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("failed to read {}: {e}", path.display())))?;
+        let batch: Self = serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("invalid Safe batch {}: {e}", path.display())))?;
+
+        if batch.chain_id != chain_id.to_string() || batch.meta.created_from_safe_address != safe {
+            return Err(Error::Config(format!(
+                "{} is a batch of Safe {} on chain {}, not of {safe} on chain {chain_id}",
+                path.display(),
+                batch.meta.created_from_safe_address,
+                batch.chain_id
+            )));
+        }
+
+        Ok(batch)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("failed to serialize Safe batch: {e}")))?;
+        fs::write(path, json)
+            .map_err(|e| Error::Config(format!("failed to write {}: {e}", path.display())))
+    }
+
+    /// Appends a call built with the contract bindings, with the given step as description.
+    pub fn push(&mut self, step: &str, tx: &TransactionRequest) -> Result<()> {
+        let to = tx
+            .to
+            .and_then(|kind| kind.to().copied())
+            .ok_or_else(|| Error::Config(format!("{step}: a Safe batch can't deploy contracts")))?;
+
+        if !self.meta.description.is_empty() {
+            self.meta.description.push_str("; ");
+        }
+        self.meta.description.push_str(step);
+
+        self.transactions.push(SafeTransaction {
+            to,
+            value: tx.value.unwrap_or(U256::ZERO).to_string(),
+            data: tx.input.input().cloned().unwrap_or_default(),
+            contract_method: None,
+            contract_inputs_values: None,
+        });
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SafeBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Safe {} batch on chain {} with {} calls",
+            self.meta.created_from_safe_address,
+            self.chain_id,
+            self.transactions.len()
+        )?;
+        for step in self
+            .meta
+            .description
+            .split("; ")
+            .filter(|step| !step.is_empty())
+        {
+            write!(f, "\n  {step}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder, RootProvider},
+    signers::local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner},
+    transports::{
+        http::{Client, Http},
+        Transport,
+    },
+};
+use log::info;
+use valence_domain_clients::clients::ethereum::EthereumClient;
+
+use crate::error::{Context, Error, Result};
+
+/// Mnemonic of the accounts anvil and hardhat fund by default. Its keys are public,
+/// so it must never sign anything on a real network.
+pub const ANVIL_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+/// Chain ids used by local development nodes.
+pub const LOCAL_CHAIN_IDS: [u64; 2] = [31337, 1337];
+
+/// Provider of the commands that only read the chain, see [`read_provider`].
+pub type ReadProvider = RootProvider<Http<Client>>;
+
+/// Number of accounts derived from [`ANVIL_MNEMONIC`] that anvil funds.
+const ANVIL_ACCOUNTS: u32 = 10;
+
+/// Where the key signing the program transactions comes from.
+#[derive(Debug, Clone)]
+pub enum SignerSource {
+    /// Encrypted JSON keystore. Without a password, it is asked for on the terminal.
+    Keystore {
+        path: PathBuf,
+        password: Option<String>,
+    },
+    /// BIP-39 mnemonic and the index of the account to derive.
+    Mnemonic { phrase: String, index: u32 },
+    /// Hex encoded raw private key.
+    PrivateKey(String),
+}
+
+impl Default for SignerSource {
+    fn default() -> Self {
+        SignerSource::Mnemonic {
+            phrase: ANVIL_MNEMONIC.to_string(),
+            index: 0,
+        }
+    }
+}
+
+impl SignerSource {
+    pub fn signer(&self) -> Result<PrivateKeySigner> {
+        match self {
+            SignerSource::Keystore { path, password } => {
+                let password = match password {
+                    Some(password) => password.clone(),
+                    None => rpassword::prompt_password(format!(
+                        "Password for keystore {}: ",
+                        path.display()
+                    ))
+                    .map_err(|e| Error::Config(format!("failed to read keystore password: {e}")))?,
+                };
+
+                PrivateKeySigner::decrypt_keystore(path, password)
+                    .map_err(Error::signing)
+                    .step(format!("decrypting keystore {}", path.display()))
+            }
+            SignerSource::Mnemonic { phrase, index } => mnemonic_signer(phrase, *index),
+            SignerSource::PrivateKey(key) => key
+                .trim()
+                .parse()
+                .map_err(|e| Error::Config(format!("invalid private key: {e}"))),
+        }
+    }
+}
+
+fn mnemonic_signer(phrase: &str, index: u32) -> Result<PrivateKeySigner> {
+    MnemonicBuilder::<English>::default()
+        .phrase(phrase)
+        .index(index)
+        .and_then(|builder| builder.build())
+        .map_err(|e| Error::Config(format!("invalid mnemonic or derivation index: {e}")))
+}
+
+/// Whether the address is one of the publicly known accounts funded by anvil.
+pub fn is_well_known_dev_account(address: Address) -> Result<bool> {
+    for index in 0..ANVIL_ACCOUNTS {
+        if mnemonic_signer(ANVIL_MNEMONIC, index)?.address() == address {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Whether the node behind `rp` is a local development chain. Anvil forks keep the
+/// chain id of the forked network, so the client version is checked as well.
+pub async fn is_local_dev_chain<T, P>(rp: &P) -> Result<bool>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let chain_id = rp.get_chain_id().await.step("querying chain id")?;
+    if LOCAL_CHAIN_IDS.contains(&chain_id) {
+        return Ok(true);
+    }
+
+    let client_version = rp
+        .get_client_version()
+        .await
+        .step("querying client version")?
+        .to_lowercase();
+
+    Ok(client_version.starts_with("anvil") || client_version.starts_with("hardhat"))
+}
+
+/// Provider for `rpc_url` without a signer, for whatever only reads the chain.
+pub fn read_provider(rpc_url: &str) -> Result<ReadProvider> {
+    let url = rpc_url
+        .parse()
+        .map_err(|e| Error::Config(format!("invalid RPC url {rpc_url}: {e}")))?;
+
+    Ok(ProviderBuilder::new().on_http(url))
+}
+
+/// Builds the Ethereum client for `rpc_url`, refusing to sign with one of the well known
+/// anvil keys unless the node is a local development chain.
+pub async fn eth_client(rpc_url: &str, source: &SignerSource) -> Result<EthereumClient> {
+    let signer = source.signer()?;
+
+    if is_well_known_dev_account(signer.address())?
+        && !is_local_dev_chain(&read_provider(rpc_url)?).await?
+    {
+        return Err(Error::Config(format!(
+            "refusing to sign with the public anvil account {} on a non local chain, \
+             use --keystore, --mnemonic or --key",
+            signer.address()
+        )));
+    }
+    info!("Signing as {}", signer.address());
+
+    Ok(EthereumClient {
+        rpc_url: rpc_url.to_string(),
+        signer,
+    })
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{Address, TxHash, B256},
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent,
+    transports::Transport,
+};
+use serde::Serialize;
+
+use crate::{
+    admin::ControlReport,
+    error::Result,
+    indexer::{execution_result_name, get_logs_batched},
+    manifest::DeploymentManifest,
+    program::{Balances, ZK_REGISTRY},
+    types::sol_types::{processor_contract::LiteProcessor, Authorization, Forwarder},
+    vk::registered_program_vk,
+};
+
+/// Forwarder configuration and when it last ran.
+///
+/// The forwarding configs (token and maximum amount) are not returned by the `config()`
+/// getter, only the accounts and the interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForwarderStatus {
+    pub processor: Address,
+    pub input_account: Address,
+    pub output_account: Address,
+    pub interval_type: &'static str,
+    pub min_interval: u64,
+    /// Block and timestamp of the last forward, zero if it never ran.
+    pub last_execution_block: u64,
+    pub last_execution_timestamp: u64,
+    /// `None` until the first forward.
+    pub blocks_since_last_execution: Option<u64>,
+}
+
+/// The ZK registry the coprocessor circuit generates messages for.
+#[derive(Debug, Clone, Serialize)]
+pub struct ZkRegistryStatus {
+    pub registry: u64,
+    /// Program VK stored in the verification gateway, zero if the registry isn't registered.
+    pub program_vk: B256,
+    pub executors: Vec<Address>,
+    pub validate_block_number: bool,
+    pub last_execution_block: u64,
+}
+
+/// Callback of a processor execution received by the Authorization contract.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallbackStatus {
+    pub execution_id: u64,
+    pub result: &'static str,
+    pub executed_count: u64,
+    pub block_number: Option<u64>,
+    pub tx_hash: Option<TxHash>,
+}
+
+/// Everything on-call needs to judge the health of the live program, read in one go.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramStatus {
+    pub manifest: DeploymentManifest,
+    pub block_number: u64,
+    pub paused: bool,
+    /// Id the next processor message gets.
+    pub execution_id: u64,
+    pub balances: Balances,
+    pub forwarder: ForwarderStatus,
+    /// Labels of the standard authorizations currently in place.
+    pub authorization_labels: Vec<String>,
+    pub zk_registry: ZkRegistryStatus,
+    pub control: ControlReport,
+    /// Most recent callbacks first.
+    pub last_callbacks: Vec<CallbackStatus>,
+}
+
+impl ProgramStatus {
+    /// Reads the state of the program in `manifest`, with its `callbacks` most recent callbacks.
+    pub async fn load<T, P>(rp: &P, manifest: DeploymentManifest, callbacks: usize) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let contracts = &manifest.contracts;
+        let authorization = Authorization::new(contracts.authorization, rp);
+        let processor = LiteProcessor::new(contracts.processor, rp);
+        let forwarder = Forwarder::new(contracts.forwarder, rp);
+
+        let block_number = rp.get_block_number().await?;
+        let from_block = manifest.first_block(rp).await?;
+
+        let config = forwarder.config().call().await?;
+        let last_execution = forwarder.lastExecution().call().await?;
+        let forwarder = ForwarderStatus {
+            processor: forwarder.processor().call().await?._0,
+            input_account: config.inputAccount,
+            output_account: config.outputAccount,
+            interval_type: match u8::from(config.intervalType) {
+                0 => "time",
+                1 => "blocks",
+                _ => "unknown",
+            },
+            min_interval: config.minInterval,
+            last_execution_block: last_execution.blockHeight,
+            last_execution_timestamp: last_execution.timestamp,
+            blocks_since_last_execution: (last_execution.blockHeight > 0)
+                .then(|| block_number.saturating_sub(last_execution.blockHeight)),
+        };
+
+        let zk_registry = ZkRegistryStatus {
+            registry: ZK_REGISTRY,
+            program_vk: registered_program_vk(rp, contracts).await?,
+            executors: authorization
+                .getZkAuthorizationsList(ZK_REGISTRY)
+                .call()
+                .await?
+                ._0,
+            validate_block_number: authorization
+                .validateBlockNumberExecution(ZK_REGISTRY)
+                .call()
+                .await?
+                ._0,
+            last_execution_block: authorization
+                .zkAuthorizationLastExecutionBlock(ZK_REGISTRY)
+                .call()
+                .await?
+                ._0,
+        };
+
+        let balances = Balances::load(rp, contracts, manifest.token).await?;
+
+        Ok(Self {
+            block_number,
+            paused: processor.paused().call().await?._0,
+            execution_id: authorization.executionId().call().await?._0,
+            balances,
+            forwarder,
+            authorization_labels: authorization_labels(rp, contracts.authorization, from_block)
+                .await?,
+            zk_registry,
+            control: ControlReport::load(rp, contracts, from_block).await?,
+            last_callbacks: last_callbacks(rp, contracts.authorization, from_block, callbacks)
+                .await?,
+            manifest,
+        })
+    }
+}
+
+/// Rebuilds the labels of the standard authorizations from the add/remove events, the
+/// contract has no way to list them.
+async fn authorization_labels<T, P>(
+    rp: &P,
+    authorization: Address,
+    from_block: u64,
+) -> Result<Vec<String>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let filter = Filter::new().address(authorization).event_signature(vec![
+        Authorization::AuthorizationAdded::SIGNATURE_HASH,
+        Authorization::AuthorizationRemoved::SIGNATURE_HASH,
+    ]);
+
+    let mut labels: Vec<String> = vec![];
+    for log in get_logs_batched(rp, &filter, from_block).await? {
+        if let Ok(event) = log.log_decode::<Authorization::AuthorizationAdded>() {
+            let label = event.inner.data.label;
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        } else if let Ok(event) = log.log_decode::<Authorization::AuthorizationRemoved>() {
+            labels.retain(|label| *label != event.inner.data.label);
+        }
+    }
+
+    Ok(labels)
+}
+
+async fn last_callbacks<T, P>(
+    rp: &P,
+    authorization: Address,
+    from_block: u64,
+    count: usize,
+) -> Result<Vec<CallbackStatus>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let filter = Filter::new()
+        .address(authorization)
+        .event_signature(Authorization::CallbackReceived::SIGNATURE_HASH);
+
+    let callbacks = get_logs_batched(rp, &filter, from_block)
+        .await?
+        .iter()
+        .rev()
+        .filter_map(|log| {
+            let event = log.log_decode::<Authorization::CallbackReceived>().ok()?;
+            let callback = event.inner.data;
+            Some(CallbackStatus {
+                execution_id: callback.executionId,
+                result: execution_result_name(u8::from(callback.executionResult)),
+                executed_count: callback.executedCount,
+                block_number: log.block_number,
+                tx_hash: log.transaction_hash,
+            })
+        })
+        .take(count)
+        .collect();
+
+    Ok(callbacks)
+}
+
+impl fmt::Display for ProgramStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.manifest)?;
+        writeln!(f)?;
+        writeln!(f, "Block:                    {}", self.block_number)?;
+        writeln!(f, "Processor paused:         {}", self.paused)?;
+        writeln!(f, "Next execution id:        {}", self.execution_id)?;
+        writeln!(f, "{}", self.balances)?;
+        writeln!(f)?;
+
+        let forwarder = &self.forwarder;
+        writeln!(f, "Forwarder:")?;
+        writeln!(f, "  processor:              {}", forwarder.processor)?;
+        writeln!(f, "  input account:          {}", forwarder.input_account)?;
+        writeln!(f, "  output account:         {}", forwarder.output_account)?;
+        writeln!(
+            f,
+            "  minimum interval:       {} {}",
+            forwarder.min_interval, forwarder.interval_type
+        )?;
+        match forwarder.blocks_since_last_execution {
+            Some(blocks) => writeln!(
+                f,
+                "  last execution:         block {} ({blocks} blocks ago, timestamp {})",
+                forwarder.last_execution_block, forwarder.last_execution_timestamp
+            )?,
+            None => writeln!(f, "  last execution:         never")?,
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Standard authorizations:")?;
+        if self.authorization_labels.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for label in &self.authorization_labels {
+            writeln!(f, "  {label}")?;
+        }
+
+        let registry = &self.zk_registry;
+        writeln!(f, "ZK registry {}:", registry.registry)?;
+        if registry.executors.is_empty() {
+            writeln!(f, "  (not registered)")?;
+        } else {
+            writeln!(f, "  program VK:             {}", registry.program_vk)?;
+            for executor in &registry.executors {
+                writeln!(f, "  executor:               {executor}")?;
+            }
+            writeln!(
+                f,
+                "  validate block number:  {}",
+                registry.validate_block_number
+            )?;
+            writeln!(
+                f,
+                "  last execution block:   {}",
+                registry.last_execution_block
+            )?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "{}", self.control)?;
+        writeln!(f)?;
+
+        writeln!(f, "Last callbacks:")?;
+        if self.last_callbacks.is_empty() {
+            write!(f, "  (none)")?;
+        } else {
+            write!(
+                f,
+                "  {:>12} {:>10}  {:<20} {:>8}  {}",
+                "Execution id", "Block", "Result", "Executed", "Transaction"
+            )?;
+        }
+        for callback in &self.last_callbacks {
+            write!(
+                f,
+                "\n  {:>12} {:>10}  {:<20} {:>8}  {}",
+                callback.execution_id,
+                callback
+                    .block_number
+                    .map(|block| block.to_string())
+                    .unwrap_or_default(),
+                callback.result,
+                callback.executed_count,
+                callback
+                    .tx_hash
+                    .map(|tx_hash| tx_hash.to_string())
+                    .unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
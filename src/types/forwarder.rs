@@ -0,0 +1,21 @@
+use alloy::sol;
+
+sol! {
+    enum IntervalType {
+        TIME,
+        BLOCKS
+    }
+
+    struct ForwardingConfig {
+        address tokenAddress;
+        uint256 maxAmount;
+    }
+
+    struct ForwarderConfig {
+        address inputAccount;
+        address outputAccount;
+        ForwardingConfig[] forwardingConfigs;
+        IntervalType intervalType;
+        uint64 minInterval;
+    }
+}
@@ -1 +1,3 @@
+pub mod forwarder;
+pub mod processor_messages;
 pub mod sol_types;
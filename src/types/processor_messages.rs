@@ -1,15 +1,8 @@
-extern crate alloc;
-
-use std::error::Error;
-use alloy::primitives::{Address, Bytes, FixedBytes};
-use alloy_sol_types::{sol, SolCall, SolValue};
-use valence_domain_clients::{
-    clients::ethereum::EthereumClient,
-    evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
+use alloy::{
+    primitives::{Address, Bytes},
+    sol,
+    sol_types::{SolCall, SolValue},
 };
-use simple_program_demo::types::sol_types::{Authorization};
-use simple_program_demo::{FORWARDER, AUTHORIZATION};
-
 
 sol! {
     /// Duration type for Valence messages
@@ -107,23 +100,16 @@ sol! {
     function forward() external;
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let mnemonic = "test test test test test test test test test test test junk";
-    let rpc_url = "http://127.0.0.1:8545";
-
-    let eth_client = EthereumClient::new(rpc_url, &mnemonic, None)?;
-    let rp = eth_client.get_request_provider().await?;
-
-    let authorization = Authorization::new(AUTHORIZATION, &rp);
-
+/// Builds the `SendMsgs` processor message that calls `forward()` on the Forwarder library
+/// in a single atomic subroutine.
+pub fn forward_message(forwarder: Address, execution_id: u64) -> ProcessorMessage {
     let forward_call = forwardCall {};
 
     // ABI encode the transfer call
     let encoded_transfer_call = forward_call.abi_encode();
 
     let atomic_function = AtomicFunction {
-        contractAddress: FORWARDER.to_string().parse().unwrap(),
+        contractAddress: forwarder,
     };
 
     // Create retry logic with NoRetry for atomic execution
@@ -140,60 +126,45 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Create AtomicSubroutine
     let atomic_subroutine = AtomicSubroutine {
-        functions: alloc::vec![atomic_function],
+        functions: vec![atomic_function],
         retryLogic: retry_logic,
     };
 
-    // Encode the atomic subroutine
-    let encoded_subroutine = atomic_subroutine.abi_encode();
-
     // Create Subroutine wrapper
     let subroutine = Subroutine {
         subroutineType: SubroutineType::Atomic,
-        subroutine: alloy_sol_types::private::Bytes::from(encoded_subroutine),
+        subroutine: Bytes::from(atomic_subroutine.abi_encode()),
     };
 
     // Create SendMsgs message with the properly encoded transfer call
     let send_msgs = SendMsgs {
-        executionId: 1, // Generated execution ID
+        executionId: execution_id,
         priority: Priority::Medium,
         subroutine,
         expirationTime: 0, // No expiration
-        messages: alloc::vec![alloy_sol_types::private::Bytes::from(encoded_transfer_call)],
+        messages: vec![Bytes::from(encoded_transfer_call)],
     };
 
-    // Encode SendMsgs
-    let encoded_send_msgs = send_msgs.abi_encode();
-
-    // Create ProcessorMessage
-    let processor_message = ProcessorMessage {
+    ProcessorMessage {
         messageType: ProcessorMessageType::SendMsgs,
-        message: alloy_sol_types::private::Bytes::from(encoded_send_msgs),
-    };
+        message: Bytes::from(send_msgs.abi_encode()),
+    }
+}
 
+/// Processor message that stops the processor from executing any further messages.
+/// Only the owner or an admin of the Authorization contract can send it.
+pub fn pause_message() -> ProcessorMessage {
+    ProcessorMessage {
+        messageType: ProcessorMessageType::Pause,
+        message: Bytes::new(),
+    }
+}
 
-    let tx = authorization
-        .addStandardAuthorizations(
-            vec!["forward".to_string()],
-            vec![vec![Address::ZERO]],
-            vec![vec![Authorization::AuthorizationData {
-                contractAddress: FORWARDER.to_string().parse().unwrap(),
-                useFunctionSelector: true,
-                functionSelector: FixedBytes::<4>::new(forwardCall::SELECTOR),
-                callHash: FixedBytes::<32>::default(),
-            }]],
-        )
-        .into_transaction_request();
-    eth_client.sign_and_send(tx).await?;
-
-    
-    let tx = authorization
-        .sendProcessorMessage(
-            "forward".to_string(),
-            Bytes::from(processor_message.abi_encode()),
-        )
-        .into_transaction_request();
-    eth_client.sign_and_send(tx).await?;
-
-    Ok(())
+/// Processor message that lifts a previous pause.
+/// Only the owner or an admin of the Authorization contract can send it.
+pub fn resume_message() -> ProcessorMessage {
+    ProcessorMessage {
+        messageType: ProcessorMessageType::Resume,
+        message: Bytes::new(),
+    }
 }
@@ -0,0 +1,257 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use log::info;
+use serde::Serialize;
+use valence_domain_clients::{clients::ethereum::EthereumClient, evm::base_client::EvmBaseClient};
+
+use crate::{
+    admin::ProgramContracts,
+    coprocessor::Coprocessor,
+    error::{Context, Error, Result},
+    gas::GasReport,
+    gateway::{self, GatewayChange},
+    manifest::DeploymentManifest,
+    program::ZK_REGISTRY,
+    types::sol_types::{Authorization, SP1VerificationGateway},
+};
+
+/// A verification key as served by the coprocessor and as stored on-chain.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct VkComparison {
+    pub coprocessor: B256,
+    pub on_chain: B256,
+}
+
+impl VkComparison {
+    pub fn in_sync(&self) -> bool {
+        self.coprocessor == self.on_chain
+    }
+}
+
+/// Domain and program VKs of the coprocessor compared with the verification gateway.
+///
+/// `executeZKMessage` reverts as soon as either of them drifts, which happens silently
+/// when the coprocessor or the controller is redeployed.
+#[derive(Debug, Clone, Serialize)]
+pub struct VkStatus {
+    pub domain_vk: VkComparison,
+    /// VK of the program registered for [`ZK_REGISTRY`] by the Authorization contract.
+    pub program_vk: VkComparison,
+    /// Addresses allowed to execute ZK messages of [`ZK_REGISTRY`], empty if it isn't registered.
+    pub zk_executors: Vec<Address>,
+}
+
+impl VkStatus {
+    /// Fetches the VKs of `app_id` from the coprocessor and compares them with the chain.
+    pub async fn load<T, P>(
+        rp: &P,
+        coprocessor: &Coprocessor,
+        app_id: &str,
+        manifest: &DeploymentManifest,
+    ) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let domain_vk = coprocessor.domain_vk().await?;
+        let program_vk = coprocessor.program_vk(app_id).await?;
+
+        Self::compare(rp, &manifest.contracts, domain_vk, program_vk).await
+    }
+
+    /// Compares the given coprocessor VKs with the ones stored on-chain.
+    pub async fn compare<T, P>(
+        rp: &P,
+        contracts: &ProgramContracts,
+        domain_vk: B256,
+        program_vk: B256,
+    ) -> Result<Self>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let zk_executors = Authorization::new(contracts.authorization, rp)
+            .getZkAuthorizationsList(ZK_REGISTRY)
+            .call()
+            .await?
+            ._0;
+
+        Ok(Self {
+            domain_vk: VkComparison {
+                coprocessor: domain_vk,
+                on_chain: gateway::domain_vk(rp, contracts.verification_gateway).await?,
+            },
+            program_vk: VkComparison {
+                coprocessor: program_vk,
+                on_chain: registered_program_vk(rp, contracts).await?,
+            },
+            zk_executors,
+        })
+    }
+
+    pub fn in_sync(&self) -> bool {
+        self.domain_vk.in_sync() && self.program_vk.in_sync()
+    }
+}
+
+impl fmt::Display for VkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, vk) in [
+            ("domain VK", self.domain_vk),
+            ("program VK", self.program_vk),
+        ] {
+            if vk.in_sync() {
+                writeln!(f, "[ok   ] {name}: {}", vk.coprocessor)?;
+            } else {
+                writeln!(
+                    f,
+                    "[DRIFT] {name}: coprocessor {}, on-chain {}",
+                    vk.coprocessor, vk.on_chain
+                )?;
+            }
+        }
+        if self.zk_executors.is_empty() {
+            write!(f, "ZK registry {ZK_REGISTRY} is not registered")
+        } else {
+            let executors: Vec<String> = self.zk_executors.iter().map(Address::to_string).collect();
+            write!(
+                f,
+                "ZK registry {ZK_REGISTRY} executors: {}",
+                executors.join(", ")
+            )
+        }
+    }
+}
+
+/// Program VK the verification gateway checks ZK messages of the Authorization contract
+/// against, zero if [`ZK_REGISTRY`] isn't registered.
+pub async fn registered_program_vk<T, P>(rp: &P, contracts: &ProgramContracts) -> Result<B256>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let vk = SP1VerificationGateway::new(contracts.verification_gateway, rp)
+        .programVKs(contracts.authorization, ZK_REGISTRY)
+        .call()
+        .await?
+        ._0;
+
+    Ok(vk)
+}
+
+/// Registers `program_vk` for [`ZK_REGISTRY`] again, keeping its executors and block number
+/// validation. The signer becomes the executor when the registry isn't registered yet.
+pub async fn update_program_vk<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    contracts: &ProgramContracts,
+    program_vk: B256,
+    gas: &mut GasReport,
+) -> Result<GatewayChange<B256>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let before = registered_program_vk(rp, contracts).await?;
+    if before == program_vk {
+        return Ok(GatewayChange {
+            setting: "program VK",
+            before,
+            after: before,
+            tx_hash: None,
+        });
+    }
+
+    let authorization = Authorization::new(contracts.authorization, rp);
+    let mut executors = authorization
+        .getZkAuthorizationsList(ZK_REGISTRY)
+        .call()
+        .await?
+        ._0;
+    if executors.is_empty() {
+        executors.push(eth_client.signer().address());
+    }
+    let validate_block_number = authorization
+        .validateBlockNumberExecution(ZK_REGISTRY)
+        .call()
+        .await?
+        ._0;
+
+    let tx = authorization
+        .addRegistries(
+            vec![ZK_REGISTRY],
+            vec![executors],
+            vec![program_vk],
+            vec![validate_block_number],
+        )
+        .into_transaction_request();
+    let receipt = gas
+        .send(eth_client, tx, "registering the program VK")
+        .await?;
+
+    let after = registered_program_vk(rp, contracts).await?;
+    if after != program_vk {
+        return Err(Error::UnexpectedState(format!(
+            "program VK is {after} after the update, expected {program_vk}"
+        )));
+    }
+    info!("Program VK updated from {before} to {after}");
+
+    Ok(GatewayChange {
+        setting: "program VK",
+        before,
+        after,
+        tx_hash: Some(receipt.transaction_hash),
+    })
+}
+
+/// Brings the on-chain VKs of `status` in line with the coprocessor ones.
+///
+/// The domain VK is updated on the verification gateway, which only its owner can do, the
+/// program VK through the Authorization contract, which needs its owner or an admin.
+pub async fn reconcile<T, P>(
+    eth_client: &EthereumClient,
+    rp: &P,
+    contracts: &ProgramContracts,
+    status: &VkStatus,
+    gas: &mut GasReport,
+) -> Result<Vec<GatewayChange<B256>>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut changes = vec![];
+
+    if !status.domain_vk.in_sync() {
+        let change = gateway::rotate_domain_vk(
+            eth_client,
+            rp,
+            contracts.verification_gateway,
+            status.domain_vk.coprocessor,
+            gas,
+        )
+        .await
+        .step("reconciling domain VK")?;
+        changes.push(change);
+    }
+
+    if !status.program_vk.in_sync() {
+        let change = update_program_vk(
+            eth_client,
+            rp,
+            contracts,
+            status.program_vk.coprocessor,
+            gas,
+        )
+        .await
+        .step("reconciling program VK")?;
+        changes.push(change);
+    }
+
+    Ok(changes)
+}
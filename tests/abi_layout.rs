@@ -0,0 +1,177 @@
+//! Checks the hand-written `sol!` structs against the contract ABIs under `src/contracts/`.
+//!
+//! Structs are compared through their EIP-712 type string, e.g.
+//! `RetryTimes(uint8 retryType,uint64 amount)`, which covers field names, order and types
+//! (enums are `uint8` on both sides).
+
+use std::{collections::BTreeMap, fs};
+
+use alloy::sol_types::SolStruct;
+use serde_json::Value;
+use simple_program_demo::types::{
+    forwarder::ForwarderConfig,
+    processor_messages::{AtomicFunction, AtomicSubroutine, Duration, RetryLogic, RetryTimes},
+};
+
+const ARTIFACTS: &[&str] = &[
+    "src/contracts/Authorization.sol/Authorization.json",
+    "src/contracts/BaseAccount.sol/BaseAccount.json",
+    "src/contracts/Forwarder.sol/Forwarder.json",
+    "src/contracts/LiteProcessor.sol/LiteProcessor.json",
+];
+
+fn abi(artifact: &str) -> Vec<Value> {
+    let contents = fs::read_to_string(artifact).unwrap();
+    let artifact: Value = serde_json::from_str(&contents).unwrap();
+
+    artifact["abi"].as_array().unwrap().clone()
+}
+
+/// Name of the struct behind a tuple parameter, e.g. `AtomicFunction[]` for
+/// `struct IProcessorMessageTypes.AtomicFunction[]`.
+fn struct_name(param: &Value) -> String {
+    let internal_type = param["internalType"].as_str().unwrap();
+    let name = internal_type.strip_prefix("struct ").unwrap();
+
+    name.rsplit('.').next().unwrap().to_string()
+}
+
+fn collect_structs(param: &Value, structs: &mut BTreeMap<String, String>) {
+    let Some(components) = param["components"].as_array() else {
+        return;
+    };
+
+    let fields: Vec<String> = components
+        .iter()
+        .map(|component| {
+            collect_structs(component, structs);
+
+            let ty = if component["type"].as_str().unwrap().starts_with("tuple") {
+                struct_name(component)
+            } else {
+                component["type"].as_str().unwrap().to_string()
+            };
+            format!("{ty} {}", component["name"].as_str().unwrap())
+        })
+        .collect();
+
+    let name = struct_name(param).trim_end_matches("[]").to_string();
+    structs.insert(name.clone(), format!("{name}({})", fields.join(",")));
+}
+
+/// EIP-712 type string of every struct used in the ABI of the artifacts.
+fn abi_structs() -> BTreeMap<String, String> {
+    let mut structs = BTreeMap::new();
+    for artifact in ARTIFACTS {
+        for item in abi(artifact) {
+            for params in ["inputs", "outputs"] {
+                for param in item[params].as_array().into_iter().flatten() {
+                    collect_structs(param, &mut structs);
+                }
+            }
+        }
+    }
+
+    structs
+}
+
+fn assert_matches_abi<S: SolStruct>(structs: &BTreeMap<String, String>) {
+    let root = S::eip712_root_type();
+    let abi = structs
+        .get(S::NAME)
+        .unwrap_or_else(|| panic!("{} is not used by any contract ABI", S::NAME));
+
+    assert_eq!(root, *abi, "{} diverges from the contract ABI", S::NAME);
+}
+
+#[test]
+fn atomic_subroutine_matches_processor_abi() {
+    let structs = abi_structs();
+
+    assert_matches_abi::<AtomicSubroutine>(&structs);
+    assert_matches_abi::<AtomicFunction>(&structs);
+    assert_matches_abi::<RetryLogic>(&structs);
+    assert_matches_abi::<RetryTimes>(&structs);
+    assert_matches_abi::<Duration>(&structs);
+}
+
+#[test]
+fn atomic_subroutine_is_the_execute_parameter() {
+    let execute = abi(ARTIFACTS[3])
+        .into_iter()
+        .find(|item| item["name"] == "_executeAtomicSubroutine")
+        .expect("LiteProcessor has no _executeAtomicSubroutine");
+
+    let param = execute["inputs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|param| param["name"] == "atomicSubroutine")
+        .expect("_executeAtomicSubroutine has no atomicSubroutine parameter");
+
+    assert_eq!(struct_name(param), AtomicSubroutine::NAME);
+}
+
+/// The public `config()` getter returns every field of the config except the arrays.
+#[test]
+fn forwarder_config_matches_getter() {
+    let config = abi(ARTIFACTS[2])
+        .into_iter()
+        .find(|item| item["name"] == "config")
+        .expect("Forwarder has no config()");
+
+    let getter: Vec<String> = config["outputs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|output| {
+            format!(
+                "{} {}",
+                output["type"].as_str().unwrap(),
+                output["name"].as_str().unwrap()
+            )
+        })
+        .collect();
+
+    let root = ForwarderConfig::eip712_root_type();
+    let fields: Vec<&str> = root
+        .trim_start_matches("ForwarderConfig(")
+        .trim_end_matches(')')
+        .split(',')
+        .filter(|field| !field.contains("[] "))
+        .collect();
+
+    assert_eq!(fields, getter);
+}
+
+/// `sol!` struct declarations of a Rust source file, keyed by name, whitespace normalized.
+fn sol_struct_declarations(path: &str) -> BTreeMap<String, String> {
+    let source = fs::read_to_string(path).unwrap();
+    let mut declarations = BTreeMap::new();
+
+    let mut rest = source.as_str();
+    while let Some(start) = rest.find("    struct ") {
+        let declaration = &rest[start..];
+        let end = declaration.find('}').unwrap() + 1;
+        let declaration = declaration[..end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let name = declaration.split_whitespace().nth(1).unwrap().to_string();
+
+        declarations.insert(name, declaration);
+        rest = &rest[start + end..];
+    }
+
+    declarations
+}
+
+/// The circuit builds the same messages from its own copy of the declarations.
+#[test]
+fn circuit_declarations_match_host() {
+    let host = sol_struct_declarations("src/types/processor_messages.rs");
+    let circuit = sol_struct_declarations("coprocessor-app/crates/circuit/src/lib.rs");
+
+    assert!(!host.is_empty());
+    assert_eq!(host, circuit);
+}
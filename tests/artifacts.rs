@@ -0,0 +1,63 @@
+//! The vendored artifacts must be the ones pinned in `src/contracts/artifacts.json`.
+
+use std::{env, fs, path::Path};
+
+use serde_json::{json, Value};
+use simple_program_demo::artifacts::{
+    artifact_path, ArtifactManifest, CompatibilityReport, ARTIFACTS_DIR, CONTRACTS,
+};
+
+#[test]
+fn vendored_artifacts_match_manifest() {
+    let dir = Path::new(ARTIFACTS_DIR);
+    let manifest = ArtifactManifest::load(dir).unwrap();
+
+    assert_eq!(manifest.artifacts.len(), CONTRACTS.len());
+    for check in manifest.check(dir).unwrap() {
+        assert!(check.ok, "{check}");
+    }
+}
+
+/// Changing a function signature or an event of a release is reported as breaking.
+#[test]
+fn changed_abi_is_incompatible() {
+    let current = Path::new(ARTIFACTS_DIR);
+    let new = env::temp_dir().join(format!("artifacts-{}", std::process::id()));
+    for contract in CONTRACTS {
+        let path = artifact_path(&new, contract);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::copy(artifact_path(current, contract), path).unwrap();
+    }
+
+    let path = artifact_path(&new, "Forwarder");
+    let mut artifact: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    for item in artifact["abi"].as_array_mut().unwrap() {
+        match item["name"].as_str() {
+            Some("updateProcessor") => item["inputs"][0]["type"] = json!("bytes32"),
+            Some("OwnershipTransferred") => item["inputs"][0]["indexed"] = json!(false),
+            _ => {}
+        }
+    }
+    fs::write(&path, artifact.to_string()).unwrap();
+
+    let report = CompatibilityReport::compare(current, &new).unwrap();
+    fs::remove_dir_all(&new).unwrap();
+
+    assert!(!report.is_compatible(), "{report}");
+    let forwarder = report
+        .contracts
+        .iter()
+        .find(|changes| changes.contract == "Forwarder")
+        .unwrap();
+    assert_eq!(forwarder.removed, ["function updateProcessor(address)"]);
+    assert_eq!(forwarder.added, ["function updateProcessor(bytes32)"]);
+    assert_eq!(forwarder.changed.len(), 1);
+    assert!(forwarder.changed[0].starts_with("event OwnershipTransferred(address,address)"));
+    for changes in report
+        .contracts
+        .iter()
+        .filter(|c| c.contract != "Forwarder")
+    {
+        assert!(!changes.is_breaking(), "{} changed", changes.contract);
+    }
+}
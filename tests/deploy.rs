@@ -0,0 +1,673 @@
+//! Deploys the whole program on a fresh anvil node and runs the standard forward.
+//!
+//! Requires `anvil` from Foundry on the `PATH`.
+
+use std::{env, fs, path::Path};
+
+use alloy::{
+    node_bindings::{Anvil, AnvilInstance},
+    primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
+use simple_program_demo::{
+    admin::OwnableContract,
+    artifacts::{verify_deployed_code, ARTIFACTS_DIR},
+    create2::{Create2Config, Create2Plan},
+    deploy::{deploy_program, verify_deployment, verify_ownership, DeployConfig, VerifierSource},
+    gas::GasReport,
+    gateway::{self, GatewayState},
+    indexer::{deployment_block, Indexer, ProgramEvent},
+    manifest::{DeploymentManifest, VerifierOrigin},
+    metrics::MetricsCollector,
+    plan::DeployPlan,
+    program::{register_zk_registry_tx, Balances, ValenceProgram, FORWARD_LABEL},
+    signer::{self, SignerSource},
+    status::ProgramStatus,
+    types::sol_types::{Authorization, MockERC20, SP1VerificationGateway},
+    vk::{self, VkStatus},
+};
+use valence_domain_clients::{
+    clients::ethereum::EthereumClient,
+    evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
+};
+
+struct Deployment {
+    // Keeps the node alive for the duration of the test
+    _anvil: AnvilInstance,
+    client: EthereumClient,
+    manifest: DeploymentManifest,
+}
+
+async fn deploy() -> Deployment {
+    let (anvil, client) = spawn_anvil().await;
+    let manifest = deploy_program(
+        &client,
+        &DeployConfig::new(VerifierSource::Mock, FixedBytes::repeat_byte(0x11)),
+    )
+    .await
+    .unwrap()
+    .manifest;
+
+    Deployment {
+        _anvil: anvil,
+        client,
+        manifest,
+    }
+}
+
+async fn spawn_anvil() -> (AnvilInstance, EthereumClient) {
+    let anvil = Anvil::new()
+        .try_spawn()
+        .expect("failed to spawn anvil, is Foundry installed?");
+
+    let client = signer::eth_client(&anvil.endpoint(), &SignerSource::default())
+        .await
+        .unwrap();
+
+    (anvil, client)
+}
+
+async fn program(deployment: &Deployment) -> ValenceProgram {
+    let client = signer::eth_client(&deployment.client.rpc_url, &SignerSource::default())
+        .await
+        .unwrap();
+
+    ValenceProgram::from_manifest(client, &deployment.manifest)
+}
+
+fn decode_logs<E: alloy::sol_types::SolEvent>(receipt: &TransactionReceipt) -> Vec<E> {
+    receipt
+        .inner
+        .logs()
+        .iter()
+        .filter_map(|log| log.log_decode::<E>().ok())
+        .map(|log| log.inner.data)
+        .collect()
+}
+
+#[tokio::test]
+async fn deployment_is_wired() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+
+    assert_eq!(
+        deployment.manifest.sp1_verifier_origin,
+        Some(VerifierOrigin::Mock)
+    );
+
+    let checks = verify_deployment(&rp, &deployment.manifest).await.unwrap();
+    for check in &checks {
+        assert!(check.ok, "{check}");
+    }
+
+    let code_checks = verify_deployed_code(&rp, &deployment.manifest, Path::new(ARTIFACTS_DIR))
+        .await
+        .unwrap();
+    for check in &code_checks {
+        assert!(check.ok, "{check}");
+    }
+
+    let deployer = deployment.client.signer().address();
+    for contract in OwnableContract::ALL {
+        let address = deployment.manifest.contracts.address_of(contract);
+        assert_eq!(
+            contract.owner(&rp, address).await.unwrap(),
+            deployer,
+            "owner of {contract}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn standard_forward_moves_tokens() {
+    let deployment = deploy().await;
+    let program = program(&deployment).await;
+    let contracts = *program.contracts();
+
+    assert_eq!(
+        program.balances().await.unwrap(),
+        Balances {
+            send_account: U256::from(1000),
+            deposit_account: U256::ZERO,
+        }
+    );
+
+    let receipt = program.authorize_forward().await.unwrap();
+    let added = decode_logs::<Authorization::AuthorizationAdded>(&receipt);
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0].label, "forward");
+
+    let receipt = program.forward_via_standard_auth().await.unwrap();
+
+    let transfers = decode_logs::<MockERC20::Transfer>(&receipt);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].from, contracts.send_account);
+    assert_eq!(transfers[0].to, contracts.deposit_account);
+    assert_eq!(transfers[0].value, U256::from(100));
+
+    let callbacks = decode_logs::<Authorization::CallbackReceived>(&receipt);
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(
+        u8::from(callbacks[0].executionResult),
+        0,
+        "execution succeeded"
+    );
+    assert_eq!(callbacks[0].executedCount, 1);
+
+    assert_eq!(
+        program.balances().await.unwrap(),
+        Balances {
+            send_account: U256::from(900),
+            deposit_account: U256::from(100),
+        }
+    );
+}
+
+#[tokio::test]
+async fn forward_is_rejected_without_authorization() {
+    let deployment = deploy().await;
+    let program = program(&deployment).await;
+
+    assert!(program.forward_via_standard_auth().await.is_err());
+    assert_eq!(
+        program.balances().await.unwrap().deposit_account,
+        U256::ZERO
+    );
+}
+
+/// Anvil ships the CREATE2 factory, so the predicted addresses can be checked end to end.
+#[tokio::test]
+async fn create2_deployment_matches_prediction() {
+    let (_anvil, client) = spawn_anvil().await;
+    let rp = client.get_request_provider().await.unwrap();
+
+    let create2 = Create2Config::new(FixedBytes::repeat_byte(0x42));
+    let mut config = DeployConfig::new(VerifierSource::Mock, FixedBytes::repeat_byte(0x11));
+    config.create2 = Some(create2);
+
+    let plan = Create2Plan::new(
+        create2,
+        client.signer().address(),
+        config.max_forward_amount,
+    );
+    let manifest = deploy_program(&client, &config).await.unwrap().manifest;
+
+    assert_eq!(manifest.create2, Some(create2));
+    let contracts = &manifest.contracts;
+    for (label, address) in [
+        ("send-account", contracts.send_account),
+        ("deposit-account", contracts.deposit_account),
+        (
+            "verification-gateway-implementation",
+            manifest.verification_gateway_implementation,
+        ),
+        ("authorization", contracts.authorization),
+        ("forwarder", contracts.forwarder),
+        ("token", manifest.token),
+    ] {
+        assert_eq!(plan.address(label).unwrap(), address, "address of {label}");
+    }
+
+    let checks = verify_deployment(&rp, &manifest).await.unwrap();
+    for check in &checks {
+        assert!(check.ok, "{check}");
+    }
+
+    // The processor and gateway are deployed by the deployer, the factory owns nothing
+    let checks = verify_ownership(&rp, &manifest, client.signer().address())
+        .await
+        .unwrap();
+    for check in &checks {
+        assert!(check.ok, "{check}");
+    }
+
+    // The same salt can't be deployed twice
+    assert!(deploy_program(&client, &config).await.is_err());
+
+    // Another salt can be handed to another owner
+    let owner = Address::repeat_byte(0x77);
+    config.create2 = Some(Create2Config::new(FixedBytes::repeat_byte(0x43)));
+    config.owner = Some(owner);
+    let manifest = deploy_program(&client, &config).await.unwrap().manifest;
+    for contract in OwnableContract::ALL {
+        let address = manifest.contracts.address_of(contract);
+        assert_eq!(contract.owner(&rp, address).await.unwrap(), owner);
+    }
+}
+
+#[tokio::test]
+async fn deployment_reports_gas() {
+    let (_anvil, client) = spawn_anvil().await;
+
+    let deployment = deploy_program(
+        &client,
+        &DeployConfig::new(VerifierSource::Mock, FixedBytes::repeat_byte(0x11)),
+    )
+    .await
+    .unwrap();
+    let gas = &deployment.gas;
+
+    // Mock verifier and 8 program contracts, then 3 calls to wire them
+    assert_eq!(gas.steps.len(), 12);
+    assert_eq!(gas.steps[0].step, "deploying SP1 verifier");
+    for step in &gas.steps {
+        assert!(
+            step.gas_used > 21_000,
+            "{} used {}",
+            step.step,
+            step.gas_used
+        );
+        assert_eq!(
+            step.cost,
+            U256::from(step.gas_used) * U256::from(step.effective_gas_price)
+        );
+    }
+    assert_eq!(
+        gas.total_gas_used,
+        gas.steps.iter().map(|step| step.gas_used).sum::<u64>()
+    );
+}
+
+/// The plan predicts the addresses of the deployment that follows it.
+#[tokio::test]
+async fn deploy_plan_matches_deployment() {
+    let (_anvil, client) = spawn_anvil().await;
+    let rp = client.get_request_provider().await.unwrap();
+
+    let config = DeployConfig::new(VerifierSource::Mock, FixedBytes::repeat_byte(0x11));
+    let plan = DeployPlan::new(&rp, client.signer().address(), &config)
+        .await
+        .unwrap();
+
+    // Contract creations that don't depend on a planned contract can be estimated
+    assert!(plan.transactions[0].transaction.gas.is_some());
+    let mint = plan
+        .transactions
+        .iter()
+        .find(|planned| planned.step == "minting DEMO to send account")
+        .unwrap();
+    assert!(mint.transaction.gas.is_none());
+    assert!(mint.gas_estimate_error.is_some());
+    // Without every estimate there is no total to report
+    assert_eq!(plan.estimated_gas(), None);
+    assert!(plan.to_string().contains("Estimated gas: unavailable"));
+
+    let deployment = deploy_program(&client, &config).await.unwrap();
+    let steps: Vec<&str> = deployment
+        .gas
+        .steps
+        .iter()
+        .map(|step| step.step.as_str())
+        .collect();
+    let planned_steps: Vec<&str> = plan
+        .transactions
+        .iter()
+        .map(|planned| planned.step.as_str())
+        .collect();
+    assert_eq!(planned_steps, steps);
+
+    let predicted = serde_json::to_value(&plan.predicted).unwrap();
+    let deployed = serde_json::to_value(&deployment.manifest).unwrap();
+    assert_eq!(predicted, deployed);
+}
+
+/// The program is handed to the final owner and the deployer keeps no role.
+#[tokio::test]
+async fn ownership_is_handed_over() {
+    let (_anvil, client) = spawn_anvil().await;
+    let rp = client.get_request_provider().await.unwrap();
+    let deployer = client.signer().address();
+    let owner = Address::repeat_byte(0x0a);
+
+    let mut config = DeployConfig::new(VerifierSource::Mock, FixedBytes::repeat_byte(0x11));
+    config.owner = Some(owner);
+    config.processor_owns_forwarder = true;
+    let manifest = deploy_program(&client, &config).await.unwrap().manifest;
+
+    let contracts = &manifest.contracts;
+    for contract in OwnableContract::ALL {
+        let expected = if contract == OwnableContract::Forwarder {
+            contracts.processor
+        } else {
+            owner
+        };
+        assert_eq!(
+            contract
+                .owner(&rp, contracts.address_of(contract))
+                .await
+                .unwrap(),
+            expected,
+            "owner of {contract}"
+        );
+    }
+
+    let checks = verify_ownership(&rp, &manifest, deployer).await.unwrap();
+    assert_eq!(checks.len(), OwnableContract::ALL.len() + 2);
+    for check in &checks {
+        assert!(check.ok, "{check}");
+    }
+
+    // The deployer can't change the program anymore
+    let program = ValenceProgram::from_manifest(client, &manifest);
+    assert!(program.authorize_forward().await.is_err());
+}
+
+/// The gateway has no getter for its domain VK, `DOMAIN_VK_SLOT` must follow its storage layout.
+#[tokio::test]
+async fn domain_vk_slot_matches_gateway_layout() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let address = deployment.manifest.contracts.verification_gateway;
+    let gateway = SP1VerificationGateway::new(address, &rp);
+
+    // Slot 0 holds the verifier, which has a getter to check the layout against
+    let verifier = rp.get_storage_at(address, U256::ZERO).await.unwrap();
+    assert_eq!(
+        Address::from_word(verifier.into()),
+        gateway.verifier().call().await.unwrap()._0
+    );
+    let domain_vk = rp
+        .get_storage_at(address, gateway::DOMAIN_VK_SLOT)
+        .await
+        .unwrap();
+    assert_eq!(FixedBytes::from(domain_vk), FixedBytes::repeat_byte(0x11));
+
+    let tx = gateway
+        .updateDomainVK(FixedBytes::repeat_byte(0x44))
+        .into_transaction_request();
+    deployment.client.sign_and_send(tx).await.unwrap();
+    let domain_vk = rp
+        .get_storage_at(address, gateway::DOMAIN_VK_SLOT)
+        .await
+        .unwrap();
+    assert_eq!(FixedBytes::from(domain_vk), FixedBytes::repeat_byte(0x44));
+}
+
+/// The gateway proxy is upgraded and reconfigured in place, keeping its address.
+#[tokio::test]
+async fn gateway_is_upgraded_and_reconfigured() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let client = &deployment.client;
+    let mut manifest = deployment.manifest.clone();
+    let address = manifest.contracts.verification_gateway;
+    let mut gas = GasReport::default();
+
+    let state = GatewayState::load(&rp, address).await.unwrap();
+    assert_eq!(state.domain_vk, FixedBytes::repeat_byte(0x11));
+    assert_eq!(
+        state.implementation,
+        manifest.verification_gateway_implementation
+    );
+
+    let upgrade = gateway::upgrade(
+        client,
+        &rp,
+        address,
+        SP1VerificationGateway::BYTECODE.clone(),
+        &mut gas,
+    )
+    .await
+    .unwrap();
+    assert_eq!(upgrade.before, manifest.verification_gateway_implementation);
+    assert_ne!(upgrade.after, upgrade.before);
+    manifest.verification_gateway_implementation = upgrade.after;
+
+    let rotation = gateway::rotate_domain_vk(
+        client,
+        &rp,
+        address,
+        FixedBytes::repeat_byte(0x22),
+        &mut gas,
+    )
+    .await
+    .unwrap();
+    assert!(rotation.tx_hash.is_some());
+    assert_eq!(
+        gateway::domain_vk(&rp, address).await.unwrap(),
+        FixedBytes::repeat_byte(0x22)
+    );
+
+    // Rotating to the current key sends nothing
+    let unchanged = gateway::rotate_domain_vk(
+        client,
+        &rp,
+        address,
+        FixedBytes::repeat_byte(0x22),
+        &mut gas,
+    )
+    .await
+    .unwrap();
+    assert!(unchanged.tx_hash.is_none());
+
+    let verifier = gateway::update_verifier(client, &rp, address, &VerifierSource::Mock, &mut gas)
+        .await
+        .unwrap();
+    assert_ne!(verifier.after, manifest.sp1_verifier);
+    manifest.sp1_verifier = verifier.after;
+
+    // The upgraded proxy kept its storage and the rest of the wiring
+    let checks = verify_deployment(&rp, &manifest).await.unwrap();
+    for check in &checks {
+        assert!(check.ok, "{check}");
+    }
+    assert_eq!(gas.steps.len(), 5);
+}
+
+/// Drifted coprocessor VKs are detected and written on-chain.
+#[tokio::test]
+async fn vk_drift_is_reconciled() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let contracts = &deployment.manifest.contracts;
+    let deployer = deployment.client.signer().address();
+    let mut gas = GasReport::default();
+
+    let status = VkStatus::compare(
+        &rp,
+        contracts,
+        FixedBytes::repeat_byte(0x11),
+        FixedBytes::ZERO,
+    )
+    .await
+    .unwrap();
+    assert!(status.in_sync());
+    assert!(status.zk_executors.is_empty());
+
+    let domain_vk = FixedBytes::repeat_byte(0x33);
+    let program_vk = FixedBytes::repeat_byte(0x44);
+    let status = VkStatus::compare(&rp, contracts, domain_vk, program_vk)
+        .await
+        .unwrap();
+    assert!(!status.domain_vk.in_sync());
+    assert!(!status.program_vk.in_sync());
+
+    let changes = vk::reconcile(&deployment.client, &rp, contracts, &status, &mut gas)
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let status = VkStatus::compare(&rp, contracts, domain_vk, program_vk)
+        .await
+        .unwrap();
+    assert!(status.in_sync(), "{status}");
+    assert_eq!(status.zk_executors, vec![deployer]);
+
+    // A redeployed controller keeps the executors of the registry
+    let executor = Address::repeat_byte(0x0e);
+    deployment
+        .client
+        .sign_and_send(register_zk_registry_tx(
+            &rp, contracts, executor, program_vk,
+        ))
+        .await
+        .unwrap();
+    let change = vk::update_program_vk(
+        &deployment.client,
+        &rp,
+        contracts,
+        FixedBytes::repeat_byte(0x55),
+        &mut gas,
+    )
+    .await
+    .unwrap();
+    assert_eq!(change.before, program_vk);
+    assert_eq!(
+        vk::registered_program_vk(&rp, contracts).await.unwrap(),
+        FixedBytes::repeat_byte(0x55)
+    );
+    let status = VkStatus::compare(&rp, contracts, domain_vk, FixedBytes::repeat_byte(0x55))
+        .await
+        .unwrap();
+    assert_eq!(status.zk_executors, vec![executor]);
+}
+
+/// The indexer lists the forwards and ownership changes, and drops the forwards a reorg
+/// removed from the chain.
+#[tokio::test]
+async fn indexer_lists_forwards_and_follows_reorgs() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let manifest = &deployment.manifest;
+    let program = program(&deployment).await;
+
+    program.authorize_forward().await.unwrap();
+    program.forward_via_standard_auth().await.unwrap();
+
+    let db = env::temp_dir().join(format!("program-index-{}.sqlite", std::process::id()));
+    let _ = fs::remove_file(&db);
+    let mut indexer =
+        Indexer::open(&db, manifest.chain_id, manifest.contracts, manifest.token).unwrap();
+    let start_block = deployment_block(&rp, manifest.contracts.authorization)
+        .await
+        .unwrap();
+    indexer.sync(&rp, start_block).await.unwrap();
+
+    let forwards = indexer.forwards().unwrap();
+    assert_eq!(forwards.len(), 1);
+    assert_eq!(forwards[0].result, "success");
+    assert_eq!(forwards[0].amount, U256::from(100));
+    assert_eq!(
+        indexer.events(Some("authorization-added")).unwrap().len(),
+        1
+    );
+
+    // Forward again, then revert the chain to before it
+    let snapshot: U256 = rp.raw_request("evm_snapshot".into(), ()).await.unwrap();
+    program.forward_via_standard_auth().await.unwrap();
+    indexer.sync(&rp, start_block).await.unwrap();
+    assert_eq!(indexer.forwards().unwrap().len(), 2);
+
+    let reverted: bool = rp
+        .raw_request("evm_revert".into(), (snapshot,))
+        .await
+        .unwrap();
+    assert!(reverted);
+    for _ in 0..2 {
+        let _: String = rp.raw_request("evm_mine".into(), ()).await.unwrap();
+    }
+
+    let report = indexer.sync(&rp, start_block).await.unwrap();
+    assert!(report.reorged_from.is_some());
+    assert_eq!(indexer.forwards().unwrap().len(), 1);
+
+    // Ownership changes of the Forwarder are indexed with those of the other contracts
+    let new_owner = Address::repeat_byte(0x0b);
+    let tx = OwnableContract::Forwarder.transfer_ownership_tx(
+        &rp,
+        manifest.contracts.forwarder,
+        new_owner,
+    );
+    deployment.client.sign_and_send(tx).await.unwrap();
+    indexer.sync(&rp, start_block).await.unwrap();
+    let transfers = indexer.events(Some("ownership-transferred")).unwrap();
+    let last = transfers.last().unwrap();
+    assert_eq!(last.contract, manifest.contracts.forwarder);
+    assert_eq!(
+        last.event,
+        ProgramEvent::OwnershipTransferred {
+            previous_owner: deployment.client.signer().address(),
+            new_owner,
+        }
+    );
+
+    fs::remove_file(&db).unwrap();
+}
+
+#[tokio::test]
+async fn status_summarizes_the_program() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let program = program(&deployment).await;
+
+    let status = ProgramStatus::load(&rp, deployment.manifest.clone(), 5)
+        .await
+        .unwrap();
+    assert!(status.authorization_labels.is_empty());
+    assert!(status.last_callbacks.is_empty());
+    assert_eq!(status.forwarder.blocks_since_last_execution, None);
+    assert_eq!(status.forwarder.interval_type, "blocks");
+
+    program.authorize_forward().await.unwrap();
+    program.forward_via_standard_auth().await.unwrap();
+
+    let status = ProgramStatus::load(&rp, deployment.manifest.clone(), 5)
+        .await
+        .unwrap();
+    assert_eq!(status.authorization_labels, vec![FORWARD_LABEL.to_string()]);
+    assert_eq!(status.execution_id, 1);
+    assert!(!status.paused);
+    assert_eq!(status.last_callbacks.len(), 1);
+    assert_eq!(status.last_callbacks[0].execution_id, 0);
+    assert_eq!(status.last_callbacks[0].result, "success");
+    assert!(status.forwarder.last_execution_block > 0);
+    assert_eq!(
+        status.forwarder.input_account,
+        deployment.manifest.contracts.send_account
+    );
+    assert_eq!(
+        status.balances,
+        Balances {
+            send_account: U256::from(900),
+            deposit_account: U256::from(100),
+        }
+    );
+}
+
+#[tokio::test]
+async fn metrics_count_callbacks_and_balances() {
+    let deployment = deploy().await;
+    let rp = deployment.client.get_request_provider().await.unwrap();
+    let manifest = &deployment.manifest;
+    let program = program(&deployment).await;
+
+    let mut collector = MetricsCollector::new(manifest, &[], None, 0);
+    let metrics = collector.collect(&rp).await.unwrap();
+    assert_eq!(metrics.callbacks["success"], 0);
+    assert_eq!(metrics.blocks_since_last_execution(), None);
+    assert!(!metrics.paused);
+
+    program.authorize_forward().await.unwrap();
+    program.forward_via_standard_auth().await.unwrap();
+    program.pause().await.unwrap();
+
+    let metrics = collector.collect(&rp).await.unwrap();
+    assert_eq!(metrics.callbacks["success"], 1);
+    assert_eq!(metrics.callbacks["rejected"], 0);
+    assert_eq!(metrics.blocks_since_last_execution(), Some(1));
+    assert!(metrics.paused);
+
+    let rendered = metrics.to_string();
+    assert!(rendered.contains("valence_callbacks_total{result=\"success\"} 1\n"));
+    assert!(rendered.contains("valence_processor_paused 1\n"));
+    assert!(rendered.contains(&format!(
+        "valence_account_balance{{account=\"deposit\",address=\"{}\",token=\"{}\"}} 100\n",
+        manifest.contracts.deposit_account, manifest.token
+    )));
+    assert!(!rendered.contains("valence_coprocessor_proof_seconds"));
+
+    // Callbacks already counted aren't counted again
+    let metrics = collector.collect(&rp).await.unwrap();
+    assert_eq!(metrics.callbacks["success"], 1);
+}
@@ -0,0 +1,112 @@
+//! Checks the Safe Transaction Builder batches written by `admin --safe-batch`.
+
+use std::{env, fs};
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{address, Address, Bytes, FixedBytes},
+    providers::ProviderBuilder,
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use simple_program_demo::{
+    admin::ProgramContracts,
+    program::{authorize_forward_tx, register_zk_registry_tx},
+    safe::SafeBatch,
+    types::sol_types::Authorization,
+};
+
+const SAFE: Address = address!("0x5afe5afe5afe5afe5afe5afe5afe5afe5afe5afe");
+
+fn contracts() -> ProgramContracts {
+    ProgramContracts {
+        authorization: Address::repeat_byte(1),
+        processor: Address::repeat_byte(2),
+        forwarder: Address::repeat_byte(3),
+        verification_gateway: Address::repeat_byte(4),
+        send_account: Address::repeat_byte(5),
+        deposit_account: Address::repeat_byte(6),
+    }
+}
+
+#[test]
+fn batch_holds_raw_calls() {
+    // Building the calls doesn't reach the node
+    let rp = ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap());
+    let contracts = contracts();
+
+    let mut batch = SafeBatch::new(1, SAFE);
+    batch
+        .push(
+            "Add the forward standard authorization",
+            &authorize_forward_tx(&rp, &contracts),
+        )
+        .unwrap();
+    batch
+        .push(
+            "Add the ZK registry",
+            &register_zk_registry_tx(
+                &rp,
+                &contracts,
+                Address::repeat_byte(7),
+                FixedBytes::repeat_byte(8),
+            ),
+        )
+        .unwrap();
+
+    let json = serde_json::to_value(&batch).unwrap();
+    assert_eq!(json["version"], "1.0");
+    assert_eq!(json["chainId"], "1");
+    assert_eq!(
+        json["meta"]["createdFromSafeAddress"],
+        serde_json::to_value(SAFE).unwrap()
+    );
+
+    let transactions = json["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    for transaction in transactions {
+        assert_eq!(
+            transaction["to"],
+            serde_json::to_value(contracts.authorization).unwrap()
+        );
+        assert_eq!(transaction["value"], "0");
+        assert!(transaction["contractMethod"].is_null());
+    }
+
+    let data: Bytes = serde_json::from_value(transactions[1]["data"].clone()).unwrap();
+    let call = Authorization::addRegistriesCall::abi_decode(&data, true).unwrap();
+    assert_eq!(call.registries, vec![0]);
+    assert_eq!(call.users, vec![vec![Address::repeat_byte(7)]]);
+}
+
+#[test]
+fn batch_file_accumulates_calls() {
+    let path = env::temp_dir().join(format!("safe-batch-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    for i in 0..2u8 {
+        let mut batch = SafeBatch::load_or_new(&path, 1, SAFE).unwrap();
+        let tx = TransactionRequest::default()
+            .with_to(Address::repeat_byte(i))
+            .with_input(vec![i]);
+        batch.push(&format!("call {i}"), &tx).unwrap();
+        batch.save(&path).unwrap();
+    }
+
+    let batch = SafeBatch::load_or_new(&path, 1, SAFE).unwrap();
+    assert_eq!(batch.transactions.len(), 2);
+    assert_eq!(batch.meta.description, "call 0; call 1");
+
+    // A batch is bound to its Safe and chain
+    assert!(SafeBatch::load_or_new(&path, 10, SAFE).is_err());
+    assert!(SafeBatch::load_or_new(&path, 1, Address::ZERO).is_err());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn batch_rejects_deployments() {
+    let tx = TransactionRequest::default().with_deploy_code(vec![0x00]);
+
+    assert!(SafeBatch::new(1, SAFE).push("deploy", &tx).is_err());
+}